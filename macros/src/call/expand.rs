@@ -1,16 +1,39 @@
 use super::parse::CallDef;
-use quote::quote;
+use quote::{format_ident, quote};
 
 /// See the `fn call` docs at the `lib.rs` of this crate for a high level definition.
 pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
-	let CallDef { pallet_struct, methods } = def;
+	let CallDef { pallet_struct, generics, methods } = def;
+	let (impl_generics, _, where_clause) = generics.split_for_impl();
 
 	// This is a vector of all the callable function names.
 	let fn_name = methods.iter().map(|method| &method.name).collect::<Vec<_>>();
 
+	// This is a vector of the path used to call each of the functions in `fn_name`: a method of the
+	// pallet, taken out of the runtime, for functions taking `self`, or an associated function
+	// which is given the runtime itself for functions taking `runtime: &mut T`.
+	let fn_path = methods
+		.iter()
+		.map(|method| {
+			let name = &method.name;
+			if method.with_runtime {
+				quote!(#pallet_struct::<T>::#name)
+			} else {
+				quote!(crate::support::GetPallet::<#pallet_struct<T>>::pallet_mut(runtime).#name)
+			}
+		})
+		.collect::<Vec<_>>();
+
+	// This is a vector of the `runtime` argument for the functions which take it, and nothing for
+	// the functions taking `self`.
+	let fn_runtime_arg = methods
+		.iter()
+		.map(|method| if method.with_runtime { quote!(runtime,) } else { quote!() })
+		.collect::<Vec<_>>();
+
 	// This is a nested vector of all the arguments for each of the functions in `fn_name`. It does
-	// not include the `self` or `caller: T::AccountId` parameter, which we always assume are the
-	// first two parameters to these calls.
+	// not include the `self` (or `runtime: &mut T`) or `origin: T::RuntimeOrigin` parameter, which
+	// we always assume are the first two parameters to these calls.
 	let args_name = methods
 		.iter()
 		.map(|method| method.args.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>())
@@ -23,42 +46,165 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		.map(|method| method.args.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>())
 		.collect::<Vec<_>>();
 
+	// All the argument types of all the calls. The traits implemented for `Call` below only
+	// require these types to implement the trait, rather than the `T` parameter of the enum like a
+	// `#[derive]` would. Our runtime is never `Clone + Hash + ...` itself, but its calls are.
+	let all_args_type = args_type.iter().flatten().collect::<Vec<_>>();
+
+	// Names used to bind the arguments of the right hand side call when comparing two calls.
+	let other_args_name = args_name
+		.iter()
+		.map(|args| args.iter().map(|name| format_ident!("other_{}", name)).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+
 	// This quote block creates an `enum Call` which contains all the calls exposed by our pallet,
-	// and the `Dispatch` trait logic to route a `caller` to access those functions.
+	// and the dispatch logic to route an `origin` to access those functions.
 	let dispatch_impl = quote! {
 		// The callable functions exposed by this pallet.
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
+		//
+		// The hidden `__Ignore` variant uses `T`, so that pallets without any callable function
+		// still have a valid `Call` enum. It can never be built, since `Infallible` has no value.
 		#[allow(non_camel_case_types)]
 		pub enum Call<T: Config> {
 			#(
 				#fn_name { #( #args_name: #args_type),* },
 			)*
+			#[doc(hidden)]
+			__Ignore(::core::marker::PhantomData<T>, ::core::convert::Infallible),
 		}
 
 		// Dispatch logic at the pallet level, mapping each of the items in the `Call` enum to the
-		// appropriate function call with all arguments, including the `caller`.
-		impl<T: Config> crate::support::Dispatch for #pallet_struct<T> {
-			type Caller = T::AccountId;
-			type Call = Call<T>;
-
-			fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> crate::support::DispatchResult {
-				match call {
+		// appropriate function call with all arguments, including the `origin`.
+		//
+		// The call is dispatched with access to the whole `runtime`, so that the functions which
+		// need more than the pallet itself (e.g. to dispatch other calls) can use it. The bounds of
+		// the `impl` block of the calls are carried over.
+		impl #impl_generics Call<T> #where_clause {
+			pub fn dispatch(
+				self,
+				runtime: &mut T,
+				origin: T::RuntimeOrigin,
+			) -> crate::support::DispatchResult
+			where
+				T: crate::support::GetPallet<#pallet_struct<T>>,
+			{
+				match self {
 					#(
 						Call::#fn_name { #( #args_name ),* } => {
-							self.#fn_name(
-								// Note that we assume the first argument of every call is the `caller`.
-								caller,
+							#fn_path(
+								#fn_runtime_arg
+								// Note that we assume the first argument of every call is the `origin`.
+								origin,
 								#( #args_name ),*
-							)?;
+							)
+						},
+					)*
+					Call::__Ignore(_, never) => match never {},
+				}
+			}
+		}
+	};
+
+	// This quote block exposes the name of the function of each call, so that the runtime can
+	// classify calls.
+	let call_name_impl = quote! {
+		impl<T: Config> crate::support::GetCallName for Call<T> {
+			fn function_name(&self) -> &'static str {
+				match self {
+					#(
+						Call::#fn_name { .. } => stringify!(#fn_name),
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+	};
+
+	// This quote block implements the common traits for `enum Call`, so that calls can be stored,
+	// printed, compared and hashed by the runtime.
+	let traits_impl = quote! {
+		impl<T: Config> ::core::clone::Clone for Call<T>
+		where
+			#( #all_args_type: ::core::clone::Clone, )*
+		{
+			fn clone(&self) -> Self {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => Call::#fn_name {
+							#( #args_name: ::core::clone::Clone::clone(#args_name) ),*
+						},
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		impl<T: Config> ::core::fmt::Debug for Call<T>
+		where
+			#( #all_args_type: ::core::fmt::Debug, )*
+		{
+			fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => f
+							.debug_struct(stringify!(#fn_name))
+							#( .field(stringify!(#args_name), #args_name) )*
+							.finish(),
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		impl<T: Config> ::core::cmp::PartialEq for Call<T>
+		where
+			#( #all_args_type: ::core::cmp::PartialEq, )*
+		{
+			#[allow(unreachable_patterns)]
+			fn eq(&self, other: &Self) -> bool {
+				match (self, other) {
+					#(
+						(
+							Call::#fn_name { #( #args_name ),* },
+							Call::#fn_name { #( #args_name: #other_args_name ),* },
+						) => true #( && #args_name == #other_args_name )*,
+					)*
+					(Call::__Ignore(_, never), _) => match *never {},
+					_ => false,
+				}
+			}
+		}
+
+		impl<T: Config> ::core::cmp::Eq for Call<T>
+		where
+			#( #all_args_type: ::core::cmp::Eq, )*
+		{}
+
+		impl<T: Config> ::core::hash::Hash for Call<T>
+		where
+			#( #all_args_type: ::core::hash::Hash, )*
+		{
+			fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+				::core::hash::Hash::hash(&::core::mem::discriminant(self), state);
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => {
+							#( ::core::hash::Hash::hash(#args_name, state); )*
 						},
 					)*
+					Call::__Ignore(_, never) => match *never {},
 				}
-				Ok(())
 			}
 		}
 	};
 
 	// Return the generated code.
-	dispatch_impl.into()
+	quote! {
+		#dispatch_impl
+		#call_name_impl
+		#traits_impl
+	}
+	.into()
 }
@@ -4,7 +4,7 @@ use syn::spanned::Spanned;
 // Custom keywords we match to when parsing the calls in a pallet.
 mod keyword {
 	syn::custom_keyword!(T);
-	syn::custom_keyword!(AccountId);
+	syn::custom_keyword!(RuntimeOrigin);
 }
 
 /// This object will collect all the information we need to keep while parsing the callable
@@ -14,6 +14,9 @@ pub struct CallDef {
 	/// This is the name of the pallet struct where the callable functions are implemented. We
 	/// mostly assume it is `Pallet`.
 	pub pallet_struct: syn::Ident,
+	/// These are the generics of the `impl` block of the callable functions, including its `where`
+	/// clause. They are carried over to the generated code, since some functions may rely on them.
+	pub generics: syn::Generics,
 	/// This is a list of the callable functions exposed by this pallet. See `CallVariantDef`.
 	pub methods: Vec<CallVariantDef>,
 }
@@ -23,6 +26,8 @@ pub struct CallDef {
 pub struct CallVariantDef {
 	/// The function name.
 	pub name: syn::Ident,
+	/// Whether the function takes `runtime: &mut T` rather than a variant of `self`.
+	pub with_runtime: bool,
 	/// Information on args of the function: `(name, type)`.
 	pub args: Vec<(syn::Ident, Box<syn::Type>)>,
 }
@@ -43,6 +48,9 @@ impl CallDef {
 			_ => panic!("not supported tokens"),
 		};
 
+		// The generics of the `impl` block, with its `where` clause.
+		let generics = item_impl.generics.clone();
+
 		// Here is where we will store all the callable functions.
 		let mut methods = vec![];
 		for item in item_impl.items {
@@ -50,31 +58,39 @@ impl CallDef {
 				// Here is where we will store all the args for each callable functions.
 				let mut args = vec![];
 
-				// First argument should be some variant of `self`.
-				match method.sig.inputs.first() {
-					Some(syn::FnArg::Receiver(_)) => {},
+				// First argument should be some variant of `self`, or `runtime: &mut T` for calls
+				// which need access to the whole runtime.
+				let with_runtime = match method.sig.inputs.first() {
+					Some(syn::FnArg::Receiver(_)) => false,
+					Some(syn::FnArg::Typed(arg)) => {
+						// Here we specifically check that this argument is as we expect for
+						// `runtime: &mut T`.
+						check_runtime_arg(arg)?;
+						true
+					},
 					_ => {
-						let msg = "Invalid call, first argument must be a variant of self";
+						let msg = "Invalid call, first argument must be a variant of self or \
+							`runtime: &mut T`";
 						return Err(syn::Error::new(method.sig.span(), msg))
 					},
-				}
+				};
 
-				// The second argument should be the `caller: T::AccountId` argument.
+				// The second argument should be the `origin: T::RuntimeOrigin` argument.
 				match method.sig.inputs.iter().skip(1).next() {
 					Some(syn::FnArg::Typed(arg)) => {
 						// Here we specifically check that this argument is as we expect for
-						// `caller: T::AccountId`.
-						check_caller_arg(arg)?;
+						// `origin: T::RuntimeOrigin`.
+						check_origin_arg(arg)?;
 					},
 					_ => {
-						let msg = "Invalid call, second argument should be `caller: T::AccountId`";
+						let msg = "Invalid call, second argument should be `origin: T::RuntimeOrigin`";
 						return Err(syn::Error::new(method.sig.span(), msg))
 					},
 				}
 
 				let fn_name = method.sig.ident.clone();
 
-				// Parsing the rest of the args. Skipping 2 for `self` and `caller`.
+				// Parsing the rest of the args. Skipping 2 for `self` (or `runtime`) and `origin`.
 				for arg in method.sig.inputs.iter().skip(2) {
 					// All arguments should be typed.
 					let arg = if let syn::FnArg::Typed(arg) = arg {
@@ -96,42 +112,77 @@ impl CallDef {
 				}
 
 				// Store all the function name and the arg data for the function.
-				methods.push(CallVariantDef { name: fn_name, args });
+				methods.push(CallVariantDef { name: fn_name, with_runtime, args });
 			}
 		}
 
 		// Return all callable functions for this pallet.
-		Ok(Self { pallet_struct, methods })
+		Ok(Self { pallet_struct, generics, methods })
+	}
+}
+
+/// Check runtime arg is exactly: `runtime: &mut T`.
+///
+/// This is kept strict to keep the code simple.
+pub fn check_runtime_arg(arg: &syn::PatType) -> syn::Result<()> {
+	pub struct CheckDispatchableRuntimeArg;
+	impl syn::parse::Parse for CheckDispatchableRuntimeArg {
+		fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+			input.parse::<syn::Token![&]>()?;
+			input.parse::<syn::Token![mut]>()?;
+			input.parse::<keyword::T>()?;
+			Ok(Self)
+		}
 	}
+
+	// This checks the arg name is `runtime` or `_runtime`.
+	if let syn::Pat::Ident(ident) = &*arg.pat {
+		// We also support the name as `_runtime` for when the variable is unused.
+		if &ident.ident != "runtime" && &ident.ident != "_runtime" {
+			let msg = "Invalid name for first parameter: expected `runtime: &mut T`";
+			return Err(syn::Error::new(ident.span(), msg))
+		}
+	}
+
+	// This checks the type is `&mut T` with `CheckDispatchableRuntimeArg`
+	let ty = &arg.ty;
+	syn::parse2::<CheckDispatchableRuntimeArg>(ty.to_token_stream()).map_err(|e| {
+		let msg = "Invalid type for first parameter: expected `runtime: &mut T`";
+		let mut err = syn::Error::new(ty.span(), msg);
+		err.combine(e);
+		err
+	})?;
+
+	Ok(())
 }
 
-/// Check caller arg is exactly: `caller: T::AccountId`.
+/// Check origin arg is exactly: `origin: T::RuntimeOrigin`.
 ///
 /// This is kept strict to keep the code simple.
-pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
+pub fn check_origin_arg(arg: &syn::PatType) -> syn::Result<()> {
 	pub struct CheckDispatchableFirstArg;
 	impl syn::parse::Parse for CheckDispatchableFirstArg {
 		fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
 			input.parse::<keyword::T>()?;
 			input.parse::<syn::Token![::]>()?;
-			input.parse::<keyword::AccountId>()?;
+			input.parse::<keyword::RuntimeOrigin>()?;
 			Ok(Self)
 		}
 	}
 
-	// This checks the arg name is `caller` or `_caller`.
+	// This checks the arg name is `origin` or `_origin`.
 	if let syn::Pat::Ident(ident) = &*arg.pat {
-		// We also support the name as `_caller` for when the variable is unused.
-		if &ident.ident != "caller" && &ident.ident != "_caller" {
-			let msg = "Invalid name for second parameter: expected `caller: T::AccountId`";
+		// We also support the name as `_origin` for when the variable is unused.
+		if &ident.ident != "origin" && &ident.ident != "_origin" {
+			let msg = "Invalid name for second parameter: expected `origin: T::RuntimeOrigin`";
 			return Err(syn::Error::new(ident.span(), msg))
 		}
 	}
 
-	// This checks the type is `T::AccountId` with `CheckDispatchableFirstArg`
+	// This checks the type is `T::RuntimeOrigin` with `CheckDispatchableFirstArg`
 	let ty = &arg.ty;
 	syn::parse2::<CheckDispatchableFirstArg>(ty.to_token_stream()).map_err(|e| {
-		let msg = "Invalid type for second parameter: expected `caller: T::AccountId`";
+		let msg = "Invalid type for second parameter: expected `origin: T::RuntimeOrigin`";
 		let mut err = syn::Error::new(ty.span(), msg);
 		err.combine(e);
 		err
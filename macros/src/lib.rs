@@ -1,6 +1,19 @@
 mod call;
 mod runtime;
 
+/// Expand the callable functions of a pallet.
+///
+/// This generates code from the functions of the annotated `impl` block:
+/// - `enum Call` - with a variant for every function, holding its arguments other than the runtime
+///   and the origin.
+/// - `fn dispatch()` on `Call` - which calls the matching function of the pallet with the origin.
+/// - implements the trait `support::GetCallName` on `Call`, giving the name of the function.
+/// - implements `Clone`, `Debug`, `PartialEq`, `Eq` and `Hash` on `Call`.
+///
+/// A function which takes another call as argument, e.g. to dispatch it on behalf of someone else,
+/// takes it as a `Box<RuntimeCall>`: `Call` would otherwise contain itself through `RuntimeCall`,
+/// and have an infinite size. These functions allow `clippy::boxed_local`, since the box is
+/// required by the variant of `Call`, not by the function itself.
 #[proc_macro_attribute]
 pub fn call(
 	attr: proc_macro::TokenStream,
@@ -14,9 +27,17 @@ pub fn call(
 /// This generates function implementations on `Runtime`:
 /// - `fn new()` - which generates a new instance of the runtime, by instantiating all the pallets
 ///   included in the runtime.
+/// - `fn on_runtime_upgrade()` - which runs the storage migration of every pallet whose code
+///   expects a newer storage version than the one of the state it holds.
 /// - `fn execute_block()` - which handles basic logic for executing a block of extrinsics. It does
-///   basic actions like incrementing the block number and checking the block to be executed has a
-///   valid block number.
+///   basic actions like running due storage migrations, incrementing the block number, checking
+///   the block to be executed has a valid block number, recording the hash of its parent, checking
+///   the inherents of the block, and calling the `on_initialize` and `on_finalize` hooks of every
+///   pallet around the extrinsics. The block runs on a copy of the runtime, so that a rejected
+///   block leaves the runtime unchanged, which requires the runtime to implement `Clone`.
+/// - `fn validate_unsigned()` - which asks the pallet of an unsigned transaction whether it accepts
+///   it, both when executing a block and to pre-validate a submission.
+/// - `impl support::GetPallet` - for every pallet, giving access to the pallet from the runtime.
 ///
 /// This also generates code needed for dispatching calls to the pallets:
 /// - Note: For simplicity, we assume that the system pallet is not callable.
@@ -25,6 +46,14 @@ pub fn call(
 /// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet. Basic
 ///   logic like incrementing the nonce of the user is included in the generated code. The system
 ///   pallet is not included.
+/// - implements the trait `support::GetCallMetadata` on `RuntimeCall`, giving the name of the
+///   pallet and of the function of every call.
+///
+/// This also generates the events of the runtime:
+/// - `enum RuntimeEvent` - an "outer"-enum representing the accumulation of the events of all the
+///   pallets whose field in the `Runtime` struct has an `#[event(..)]` attribute, giving the type
+///   of their events, e.g. `#[event(sudo::Event<types::AccountId>)]`.
+/// - implements `From` the event of each of these pallets for `RuntimeEvent`.
 #[proc_macro_attribute]
 pub fn runtime(
 	attr: proc_macro::TokenStream,
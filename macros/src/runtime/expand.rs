@@ -3,12 +3,15 @@ use quote::quote;
 
 /// See the `fn runtime` docs at the `lib.rs` of this crate for a high level definition.
 pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
-	let RuntimeDef { runtime_struct, pallets } = def;
+	let RuntimeDef { runtime_struct, pallets, events } = def;
 
 	// This is a vector of all the pallet names, not including system.
 	let pallet_names = pallets.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
 	// This is a vector of all the pallet types, not including system.
 	let pallet_types = pallets.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
+	// These are the names of the pallets which deposit events, and the types of their events.
+	let event_names = events.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+	let event_types = events.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
 
 	// This quote block implements functions on the `Runtime` struct.
 	let runtime_impl = quote! {
@@ -24,24 +27,169 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 				}
 			}
 
-			// Execute a block of extrinsics. Increments the block number.
+			// Migrate the storage of every pallet whose code expects a newer storage version than
+			// the version of the state it holds.
+			fn on_runtime_upgrade(&mut self) {
+				use crate::support::OnRuntimeUpgrade;
+				if <system::Pallet::<Self> as OnRuntimeUpgrade>::STORAGE_VERSION
+					> self.system.on_chain_storage_version()
+				{
+					self.system.on_runtime_upgrade();
+				}
+				#(
+					if <#pallet_types as OnRuntimeUpgrade>::STORAGE_VERSION
+						> self.#pallet_names.on_chain_storage_version()
+					{
+						self.#pallet_names.on_runtime_upgrade();
+					}
+				)*
+			}
+
+			// Execute a block of extrinsics. Runs the storage migrations which are due, increments
+			// the block number, and records the hash of the parent block.
+			// The inherents are dispatched with the none origin, and the block is rejected if one of
+			// them fails. So are the unsigned transactions, which the block must only contain if
+			// their pallet accepts them. The errors of the other extrinsics are only reported.
+			// The block is executed on a copy of the runtime, which replaces it only if the block is
+			// not rejected, so that a rejected block leaves the runtime unchanged.
 			fn execute_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
+				Self::check_inherents(&block)?;
+				let mut runtime = self.clone();
+				runtime.apply_block(block)?;
+				*self = runtime;
+				Ok(())
+			}
+
+			// Apply the changes of the block to the runtime, which may be left partially changed if
+			// the block is rejected.
+			fn apply_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
+				self.on_runtime_upgrade();
 				self.system.inc_block_number();
 				if block.header.block_number != self.system.block_number() {
 					return Err(&"block number does not match what is expected")
 				}
+				self.system.set_parent_hash(block.header.parent_hash);
+				self.on_initialize(block.header.block_number);
+				// The unsigned transactions are all validated before any extrinsic is dispatched.
+				for support::Extrinsic { caller, call } in &block.extrinsics {
+					if caller.is_none() && !Self::is_inherent(call) {
+						self.validate_unsigned(call)?;
+					}
+				}
 				for (i, support::Extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
-					self.system.inc_nonce(&caller);
-					let _res = self.dispatch(caller, call).map_err(|e| {
+					let origin = match caller {
+						Some(caller) => {
+							self.system.inc_nonce(&caller);
+							support::Origin::Signed(caller)
+						},
+						None if Self::is_inherent(&call) => {
+							self.dispatch(support::Origin::None.into(), call)?;
+							continue
+						},
+						None => support::Origin::None,
+					};
+					let _res = self.dispatch(origin.into(), call).map_err(|e| {
 						eprintln!(
 							"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
 							block.header.block_number, i, e
 						)
 					});
 				}
+				self.on_finalize(block.header.block_number);
+				Ok(())
+			}
+
+			// Check that the inherents come before the other extrinsics, and that the block contains
+			// an inherent of every pallet requiring one.
+			fn check_inherents(block: &types::Block) -> crate::support::DispatchResult {
+				use crate::support::ProvideInherent;
+				let is_inherent = |extrinsic: &types::Extrinsic| {
+					extrinsic.caller.is_none() && Self::is_inherent(&extrinsic.call)
+				};
+				let inherents = block.extrinsics.iter().take_while(|extrinsic| is_inherent(extrinsic));
+				if block.extrinsics.iter().skip(inherents.count()).any(is_inherent) {
+					return Err("inherents must come before the other extrinsics")
+				}
+				#(
+					if <#pallet_types as ProvideInherent<#pallet_names::Call<Self>>>::INHERENT_REQUIRED
+						&& !block.extrinsics.iter().any(|extrinsic| {
+							is_inherent(extrinsic)
+								&& matches!(extrinsic.call, RuntimeCall::#pallet_names(_))
+						})
+					{
+						return Err(concat!("block is missing the inherent of ", stringify!(#pallet_names)))
+					}
+				)*
 				Ok(())
 			}
+
+			// Check whether the pallet of the unsigned `call` accepts it, and with which priority and
+			// longevity. This is also used to validate an unsigned transaction before it is
+			// submitted.
+			pub fn validate_unsigned(
+				&self,
+				call: &RuntimeCall,
+			) -> crate::support::TransactionValidity {
+				use crate::support::ValidateUnsigned;
+				match call {
+					#(
+						RuntimeCall::#pallet_names(call) => {
+							<#pallet_types as ValidateUnsigned<Self, _>>::validate_unsigned(self, call)
+						},
+					)*
+				}
+			}
+
+			// Check whether `call` is an inherent of the pallet it belongs to.
+			fn is_inherent(call: &RuntimeCall) -> bool {
+				use crate::support::ProvideInherent;
+				match call {
+					#(
+						RuntimeCall::#pallet_names(call) => {
+							<#pallet_types as ProvideInherent<_>>::is_inherent(call)
+						},
+					)*
+				}
+			}
+
+			// Call the `on_initialize` hook of every pallet, in the order they are declared.
+			fn on_initialize(&mut self, n: <Self as system::Config>::BlockNumber) {
+				use crate::support::Hooks;
+				<system::Pallet::<Self> as Hooks<Self, _>>::on_initialize(self, n);
+				#( <#pallet_types as Hooks<Self, _>>::on_initialize(self, n); )*
+			}
+
+			// Call the `on_finalize` hook of every pallet, in the order they are declared.
+			fn on_finalize(&mut self, n: <Self as system::Config>::BlockNumber) {
+				use crate::support::Hooks;
+				<system::Pallet::<Self> as Hooks<Self, _>>::on_finalize(self, n);
+				#( <#pallet_types as Hooks<Self, _>>::on_finalize(self, n); )*
+			}
+		}
+
+		// Give access to each pallet of the runtime, for the code which runs with the whole
+		// runtime, like the block hooks.
+		impl crate::support::GetPallet<system::Pallet<#runtime_struct>> for #runtime_struct {
+			fn pallet(&self) -> &system::Pallet<#runtime_struct> {
+				&self.system
+			}
+
+			fn pallet_mut(&mut self) -> &mut system::Pallet<#runtime_struct> {
+				&mut self.system
+			}
 		}
+
+		#(
+			impl crate::support::GetPallet<#pallet_types> for #runtime_struct {
+				fn pallet(&self) -> &#pallet_types {
+					&self.#pallet_names
+				}
+
+				fn pallet_mut(&mut self) -> &mut #pallet_types {
+					&mut self.#pallet_names
+				}
+			}
+		)*
 	};
 
 	// This quote block implements the `RuntimeCall` enum and implements the `Dispatch` trait.
@@ -51,21 +199,22 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
+		#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 		pub enum RuntimeCall {
 			#( #pallet_names(#pallet_names::Call<#runtime_struct>) ),*
 		}
 
 		impl crate::support::Dispatch for #runtime_struct {
-			type Caller = <Runtime as system::Config>::AccountId;
+			type Origin = <#runtime_struct as system::Config>::RuntimeOrigin;
 			type Call = RuntimeCall;
-			// Dispatch a call on behalf of a caller. Increments the caller's nonce.
+			// Dispatch a call on behalf of an origin.
 			//
 			// Dispatch allows us to identify which underlying pallet call we want to execute.
-			// Note that we extract the `caller` from the extrinsic, and use that information
-			// to determine who we are executing the call on behalf of.
+			// Note that for extrinsics, the `origin` is built from the `caller` of the extrinsic,
+			// and we use that information to determine who we are executing the call on behalf of.
 			fn dispatch(
 				&mut self,
-				caller: Self::Caller,
+				origin: Self::Origin,
 				runtime_call: Self::Call,
 			) -> crate::support::DispatchResult {
 				// This match statement will allow us to correctly route `RuntimeCall`s
@@ -73,7 +222,7 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 				match runtime_call {
 					#(
 						RuntimeCall::#pallet_names(call) => {
-							self.#pallet_names.dispatch(caller, call)?;
+							call.dispatch(self, origin)?;
 						}
 					),*
 				}
@@ -82,9 +231,49 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		}
 	};
 
+	// This quote block implements the `RuntimeEvent` enum, and its conversions from the events of
+	// each pallet.
+	let event_impl = quote! {
+		// These are all the events which can be deposited by the pallets of the runtime.
+		// Note that it is just an accumulation of the events of each pallet declaring some.
+		#[allow(non_camel_case_types)]
+		#[derive(Debug, Clone, PartialEq, Eq)]
+		pub enum RuntimeEvent {
+			#( #event_names(#event_types) ),*
+		}
+
+		#(
+			impl From<#event_types> for RuntimeEvent {
+				fn from(event: #event_types) -> Self {
+					RuntimeEvent::#event_names(event)
+				}
+			}
+		)*
+	};
+
+	// This quote block implements `GetCallMetadata` on the `RuntimeCall` enum, combining the name
+	// of the pallet with the name of the function of the pallet level call.
+	let call_metadata_impl = quote! {
+		impl crate::support::GetCallMetadata for RuntimeCall {
+			fn call_metadata(&self) -> crate::support::CallMetadata {
+				use crate::support::GetCallName;
+				match self {
+					#(
+						RuntimeCall::#pallet_names(call) => crate::support::CallMetadata {
+							pallet_name: stringify!(#pallet_names),
+							function_name: call.function_name(),
+						},
+					)*
+				}
+			}
+		}
+	};
+
 	// We combine and return all the generated code.
 	quote! {
 		#dispatch_impl
+		#event_impl
+		#call_metadata_impl
 		#runtime_impl
 	}
 	.into()
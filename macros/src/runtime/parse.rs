@@ -8,10 +8,15 @@ pub struct RuntimeDef {
 	/// This is the list of pallets included in the `Runtime` struct. We omit `system` from this
 	/// list, but during parsing we check that system exists.
 	pub pallets: Vec<(syn::Ident, syn::Type)>,
+	/// This is the list of pallets which deposit events, with the type of their events, as given
+	/// by the `#[event(..)]` attribute of their field in the `Runtime` struct.
+	pub events: Vec<(syn::Ident, syn::Type)>,
 }
 
 impl RuntimeDef {
-	pub fn try_from(item: syn::Item) -> syn::Result<Self> {
+	/// Parse the `Runtime` struct. The `#[event(..)]` attributes are removed from its fields, since
+	/// they are only meaningful to this macro.
+	pub fn try_from(item: &mut syn::Item) -> syn::Result<Self> {
 		// First we check that we are parsing a `struct`.
 		let item_struct = if let syn::Item::Struct(item) = item {
 			item
@@ -20,20 +25,24 @@ impl RuntimeDef {
 		};
 
 		// We check that the `Runtime` includes the `system` pallet as the first item.
-		check_system(&item_struct)?;
+		check_system(item_struct)?;
 
-		let runtime_struct = item_struct.ident;
+		let runtime_struct = item_struct.ident.clone();
 
-		// Here is where we will store a list of all the pallets.
+		// Here is where we will store a list of all the pallets, and of their events.
 		let mut pallets = vec![];
+		let mut events = vec![];
 		// We skip `system`, which we ensure is the first field in `check_system`.
-		for field in item_struct.fields.into_iter().skip(1) {
-			if let Some(ident) = field.ident {
-				pallets.push((ident, field.ty))
+		for field in item_struct.fields.iter_mut().skip(1) {
+			let Some(ident) = field.ident.clone() else { continue };
+			for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("event")) {
+				events.push((ident.clone(), attr.parse_args::<syn::Type>()?));
 			}
+			field.attrs.retain(|attr| !attr.path().is_ident("event"));
+			pallets.push((ident, field.ty.clone()))
 		}
 
-		Ok(Self { runtime_struct, pallets })
+		Ok(Self { runtime_struct, pallets, events })
 	}
 }
 
@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+
+use num::{CheckedAdd, CheckedSub, Zero};
+
+use crate::{
+	support::{DispatchResult, Hooks, OnRuntimeUpgrade, ProvideInherent, ValidateUnsigned},
+	system::{ensure_signed, Config as SystemConfig},
+};
+
+pub trait Config: SystemConfig {
+	/// The identifier of an asset.
+	type AssetId: Ord + Copy;
+	/// The type of the balances of the assets.
+	type Balance: CheckedSub + CheckedAdd + Zero + Copy;
+}
+
+/// The details of an asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetDetails<AccountId, Balance> {
+	/// The account which created the asset, and which can mint and burn it.
+	pub owner: AccountId,
+	/// The total amount of the asset held by all the accounts.
+	pub supply: Balance,
+}
+
+/// The metadata describing an asset to users.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AssetMetadata {
+	/// The name of the asset, e.g. "Test Dollar".
+	pub name: String,
+	/// The ticker symbol of the asset, e.g. "TUSD".
+	pub symbol: String,
+	/// The number of decimals used to display a balance of the asset.
+	pub decimals: u8,
+}
+
+/// This is the Assets Module.
+/// It keeps track of the balances of any number of fungible assets, identified by an `AssetId`,
+/// next to the native balance of the Balances Pallet.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The details of each asset.
+	assets: BTreeMap<T::AssetId, AssetDetails<T::AccountId, T::Balance>>,
+	/// The metadata of each asset.
+	metadata: BTreeMap<T::AssetId, AssetMetadata>,
+	/// The balance of each account, by asset.
+	accounts: BTreeMap<(T::AssetId, T::AccountId), T::Balance>,
+	/// The amount of an asset a delegate can transfer on behalf of an owner, by asset, owner and
+	/// delegate.
+	#[allow(clippy::type_complexity)]
+	approvals: BTreeMap<(T::AssetId, T::AccountId, T::AccountId), T::Balance>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Create the asset `id`, owned by the caller, with no supply.
+	pub fn create(&mut self, origin: T::RuntimeOrigin, id: T::AssetId) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		if self.assets.contains_key(&id) {
+			return Err("Asset already exists")
+		}
+
+		self.assets
+			.insert(id, AssetDetails { owner: caller, supply: T::Balance::zero() });
+		Ok(())
+	}
+
+	/// Set the metadata of the asset `id`.
+	/// This function fails if the caller is not the owner of the asset.
+	pub fn set_metadata(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		id: T::AssetId,
+		metadata: AssetMetadata,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.ensure_owner(id, &caller)?;
+
+		self.metadata.insert(id, metadata);
+		Ok(())
+	}
+
+	/// Create `amount` of the asset `id`, credited to `beneficiary`.
+	/// This function fails if the caller is not the owner of the asset.
+	pub fn mint(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		id: T::AssetId,
+		beneficiary: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let details = self.ensure_owner(id, &caller)?;
+
+		let new_supply = details.supply.checked_add(&amount).ok_or("Fund overflow.")?;
+		let new_balance =
+			self.balance(id, &beneficiary).checked_add(&amount).ok_or("Fund overflow.")?;
+
+		self.set_supply(id, new_supply);
+		self.set_balance(id, &beneficiary, new_balance);
+		Ok(())
+	}
+
+	/// Destroy `amount` of the asset `id`, debited from `who`.
+	/// This function fails if the caller is not the owner of the asset.
+	pub fn burn(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		id: T::AssetId,
+		who: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let details = self.ensure_owner(id, &caller)?;
+
+		let new_balance = self.balance(id, &who).checked_sub(&amount).ok_or("Not enough funds.")?;
+		let new_supply = details.supply.checked_sub(&amount).ok_or("Not enough funds.")?;
+
+		self.set_supply(id, new_supply);
+		self.set_balance(id, &who, new_balance);
+		Ok(())
+	}
+
+	/// Transfer `amount` of the asset `id` from the caller to `to`.
+	pub fn transfer(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		id: T::AssetId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.do_transfer(id, &caller, &to, amount)
+	}
+
+	/// Allow `delegate` to transfer up to `amount` of the asset `id` on behalf of the caller,
+	/// replacing any previous approval.
+	pub fn approve_transfer(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		id: T::AssetId,
+		delegate: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.asset(id).ok_or("Unknown asset")?;
+
+		self.approvals.insert((id, caller, delegate), amount);
+		Ok(())
+	}
+
+	/// Transfer `amount` of the asset `id` from `owner` to `destination`, using the approval
+	/// given by `owner` to the caller.
+	pub fn transfer_approved(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		id: T::AssetId,
+		owner: T::AccountId,
+		destination: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let new_allowance = self
+			.allowance(id, &owner, &caller)
+			.checked_sub(&amount)
+			.ok_or("Not enough allowance.")?;
+
+		self.do_transfer(id, &owner, &destination, amount)?;
+		self.approvals.insert((id, owner, caller), new_allowance);
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Assets Pallet.
+	pub fn new() -> Self {
+		Self {
+			assets: BTreeMap::new(),
+			metadata: BTreeMap::new(),
+			accounts: BTreeMap::new(),
+			approvals: BTreeMap::new(),
+		}
+	}
+
+	/// Get the details of the asset `id`, if it exists.
+	pub fn asset(&self, id: T::AssetId) -> Option<&AssetDetails<T::AccountId, T::Balance>> {
+		self.assets.get(&id)
+	}
+
+	/// Get the metadata of the asset `id`, if it was set.
+	pub fn metadata(&self, id: T::AssetId) -> Option<&AssetMetadata> {
+		self.metadata.get(&id)
+	}
+
+	/// Get the balance of the asset `id` held by `who`.
+	/// If the account has no stored balance, we return zero.
+	pub fn balance(&self, id: T::AssetId, who: &T::AccountId) -> T::Balance {
+		*self.accounts.get(&(id, who.clone())).unwrap_or(&T::Balance::zero())
+	}
+
+	/// Get the amount of the asset `id` which `delegate` can transfer on behalf of `owner`.
+	pub fn allowance(
+		&self,
+		id: T::AssetId,
+		owner: &T::AccountId,
+		delegate: &T::AccountId,
+	) -> T::Balance {
+		*self
+			.approvals
+			.get(&(id, owner.clone(), delegate.clone()))
+			.unwrap_or(&T::Balance::zero())
+	}
+
+	/// Get the details of the asset `id`, checking that `who` is its owner.
+	fn ensure_owner(
+		&self,
+		id: T::AssetId,
+		who: &T::AccountId,
+	) -> Result<&AssetDetails<T::AccountId, T::Balance>, &'static str> {
+		let details = self.asset(id).ok_or("Unknown asset")?;
+		if &details.owner != who {
+			return Err("Caller is not the owner of the asset")
+		}
+		Ok(details)
+	}
+
+	/// Transfer `amount` of the asset `id` from `from` to `to`.
+	fn do_transfer(
+		&mut self,
+		id: T::AssetId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		self.asset(id).ok_or("Unknown asset")?;
+
+		let new_from_balance =
+			self.balance(id, from).checked_sub(&amount).ok_or("Not enough funds.")?;
+		if from == to {
+			return Ok(())
+		}
+		let new_to_balance = self.balance(id, to).checked_add(&amount).ok_or("Fund overflow.")?;
+
+		self.set_balance(id, from, new_from_balance);
+		self.set_balance(id, to, new_to_balance);
+		Ok(())
+	}
+
+	fn set_balance(&mut self, id: T::AssetId, who: &T::AccountId, amount: T::Balance) {
+		self.accounts.insert((id, who.clone()), amount);
+	}
+
+	fn set_supply(&mut self, id: T::AssetId, supply: T::Balance) {
+		if let Some(details) = self.assets.get_mut(&id) {
+			details.supply = supply;
+		}
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::support::Origin;
+
+	struct TestConfig;
+
+	impl SystemConfig for TestConfig {
+		type AccountId = String;
+		type RuntimeOrigin = Origin<String>;
+		type RuntimeEvent = ();
+		type BlockNumber = u32;
+		type Nonce = u32;
+	}
+
+	impl Config for TestConfig {
+		type AssetId = u32;
+		type Balance = u128;
+	}
+
+	const ASSET: u32 = 1;
+
+	#[test]
+	fn create_asset() {
+		// Arrange
+		let (mut assets, alice, bob) = setup();
+		// Act
+		let result = assets.create(Origin::Signed(bob.clone()), 2);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(assets.asset(2), Some(&AssetDetails { owner: bob.clone(), supply: 0 }));
+		assert_eq!(assets.create(Origin::Signed(bob), ASSET), Err("Asset already exists"));
+		assert_eq!(assets.asset(ASSET).map(|details| &details.owner), Some(&alice));
+	}
+
+	#[test]
+	fn set_metadata() {
+		// Arrange
+		let (mut assets, alice, bob) = setup();
+		let metadata = AssetMetadata {
+			name: String::from("Test Dollar"),
+			symbol: String::from("TUSD"),
+			decimals: 2,
+		};
+		// Act
+		let result = assets.set_metadata(Origin::Signed(alice), ASSET, metadata.clone());
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(assets.metadata(ASSET), Some(&metadata));
+		assert_eq!(
+			assets.set_metadata(Origin::Signed(bob), ASSET, AssetMetadata::default()),
+			Err("Caller is not the owner of the asset")
+		);
+	}
+
+	#[test]
+	fn mint_and_burn() {
+		// Arrange
+		let (mut assets, alice, bob) = setup();
+		// Act
+		let mint = assets.mint(Origin::Signed(alice.clone()), ASSET, bob.clone(), 100);
+		let burn = assets.burn(Origin::Signed(alice.clone()), ASSET, bob.clone(), 30);
+		// Assert
+		assert_eq!(mint, Ok(()));
+		assert_eq!(burn, Ok(()));
+		assert_eq!(assets.balance(ASSET, &bob), 70);
+		assert_eq!(assets.asset(ASSET).map(|details| details.supply), Some(70));
+		assert_eq!(
+			assets.burn(Origin::Signed(alice.clone()), ASSET, bob.clone(), 80),
+			Err("Not enough funds.")
+		);
+		assert_eq!(
+			assets.mint(Origin::Signed(bob.clone()), ASSET, bob.clone(), 10),
+			Err("Caller is not the owner of the asset")
+		);
+		assert_eq!(assets.mint(Origin::Signed(alice), 2, bob, 10), Err("Unknown asset"));
+	}
+
+	#[test]
+	fn mint_overflow() {
+		// Arrange
+		let (mut assets, alice, bob) = setup();
+		let _ = assets.mint(Origin::Signed(alice.clone()), ASSET, alice.clone(), u128::MAX);
+		// Act
+		let result = assets.mint(Origin::Signed(alice), ASSET, bob.clone(), 1);
+		// Assert
+		assert_eq!(result, Err("Fund overflow."));
+		assert_eq!(assets.balance(ASSET, &bob), 0);
+	}
+
+	#[test]
+	fn transfer_asset() {
+		// Arrange
+		let (mut assets, alice, bob) = setup();
+		let _ = assets.mint(Origin::Signed(alice.clone()), ASSET, alice.clone(), 100);
+		// Act
+		let result = assets.transfer(Origin::Signed(alice.clone()), ASSET, bob.clone(), 40);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(assets.balance(ASSET, &alice), 60);
+		assert_eq!(assets.balance(ASSET, &bob), 40);
+		assert_eq!(
+			assets.transfer(Origin::Signed(bob.clone()), ASSET, alice.clone(), 50),
+			Err("Not enough funds.")
+		);
+		assert_eq!(
+			assets.transfer(Origin::Signed(alice.clone()), ASSET, alice.clone(), 60),
+			Ok(())
+		);
+		assert_eq!(assets.balance(ASSET, &alice), 60);
+	}
+
+	#[test]
+	fn transfer_approved() {
+		// Arrange
+		let (mut assets, alice, bob) = setup();
+		let charlie = String::from("charlie");
+		let _ = assets.mint(Origin::Signed(alice.clone()), ASSET, alice.clone(), 100);
+		let _ = assets.approve_transfer(Origin::Signed(alice.clone()), ASSET, bob.clone(), 50);
+		// Act
+		let result = assets.transfer_approved(
+			Origin::Signed(bob.clone()),
+			ASSET,
+			alice.clone(),
+			charlie.clone(),
+			30,
+		);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(assets.balance(ASSET, &alice), 70);
+		assert_eq!(assets.balance(ASSET, &charlie), 30);
+		assert_eq!(assets.allowance(ASSET, &alice, &bob), 20);
+		assert_eq!(
+			assets.transfer_approved(Origin::Signed(bob), ASSET, alice, charlie, 30),
+			Err("Not enough allowance.")
+		);
+	}
+
+	fn setup() -> (
+		Pallet<TestConfig>,
+		<TestConfig as SystemConfig>::AccountId,
+		<TestConfig as SystemConfig>::AccountId,
+	) {
+		let mut assets = Pallet::new();
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		assets
+			.create(Origin::Signed(alice.clone()), ASSET)
+			.expect("the asset does not exist yet");
+
+		(assets, alice, bob)
+	}
+}
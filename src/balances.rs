@@ -1,19 +1,49 @@
 use std::collections::BTreeMap;
 
-use num::{CheckedAdd, CheckedSub, Zero};
+use num::{CheckedAdd, CheckedSub, Saturating, Zero};
 
-use crate::system::Config as SystemConfig;
+use crate::{
+	support::{
+		DispatchResult, Hooks, OnRuntimeUpgrade, ProvideInherent, StorageVersion, ValidateUnsigned,
+	},
+	system::{ensure_root, ensure_signed, Config as SystemConfig},
+};
 
 pub trait Config: SystemConfig {
-	type Balance: CheckedSub + CheckedAdd + Zero + Copy;
+	type Balance: CheckedSub + CheckedAdd + Saturating + Zero + Copy + Ord;
+}
+
+/// The identifier of a lock on the balance of an account, e.g. `*b"vesting "`.
+pub type LockIdentifier = [u8; 8];
+
+/// The balance of an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountData<Balance> {
+	/// The balance which the account can freely use.
+	pub free: Balance,
+	/// The balance which is set aside, and can not be used until it is unreserved.
+	pub reserved: Balance,
 }
 
 /// This is the Balances Module
 /// It is a simple module which keeps track of how much balance each account has in this state
 /// machine.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
+	/// The version of the storage layout of the state held by this pallet.
+	storage_version: StorageVersion,
+	/// The free balance of each account, as stored up to storage version 0.
+	/// The migration to version 1 moves these balances into `accounts`, so it is always empty
+	/// afterwards.
 	balances: BTreeMap<T::AccountId, T::Balance>,
+	/// The free and reserved balance of each account.
+	accounts: BTreeMap<T::AccountId, AccountData<T::Balance>>,
+	/// The locks on the free balance of each account, by identifier.
+	locks: BTreeMap<T::AccountId, BTreeMap<LockIdentifier, T::Balance>>,
+	/// The amount a spender can transfer on behalf of an owner, by owner and spender.
+	allowances: BTreeMap<(T::AccountId, T::AccountId), T::Balance>,
+	/// The total balance of all the accounts, free and reserved, as of storage version 2.
+	total_issuance: T::Balance,
 }
 
 #[macros::call]
@@ -23,18 +53,57 @@ impl<T: Config> Pallet<T> {
 	/// and that no mathematical overflows occur.
 	pub fn transfer(
 		&mut self,
-		caller: T::AccountId,
+		origin: T::RuntimeOrigin,
 		to: T::AccountId,
 		amount: T::Balance,
 	) -> Result<(), &'static str> {
-		let caller_balance = self.balance(&caller);
-		let to_balance = self.balance(&to);
+		let caller = ensure_signed(origin)?;
+		self.do_transfer(&caller, &to, amount)
+	}
+
+	/// Allow `spender` to transfer up to `amount` on behalf of the caller, replacing any previous
+	/// allowance.
+	pub fn approve(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		spender: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.allowances.insert((caller, spender), amount);
+		Ok(())
+	}
 
-		let new_from_balance = caller_balance.checked_sub(&amount).ok_or("Not enough funds.")?;
-		let new_to_balance = to_balance.checked_add(&amount).ok_or("Fund overflow.")?;
+	/// Transfer `amount` from `owner` to `to`, using the allowance given by `owner` to the caller.
+	/// The allowance is decreased by `amount`.
+	pub fn transfer_from(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		owner: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let new_allowance = self
+			.allowance(&owner, &caller)
+			.checked_sub(&amount)
+			.ok_or("Not enough allowance.")?;
+
+		self.do_transfer(&owner, &to, amount)?;
+		self.allowances.insert((owner, caller), new_allowance);
+		Ok(())
+	}
 
-		self.set_balance(&caller, new_from_balance);
-		self.set_balance(&to, new_to_balance);
+	/// Set the free balance of the account `who` to `amount`.
+	/// This is an administrative call, which can only be made by the root origin.
+	pub fn force_set_balance(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		who: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		ensure_root(origin)?;
+		self.set_balance(&who, amount);
 		Ok(())
 	}
 }
@@ -42,30 +111,245 @@ impl<T: Config> Pallet<T> {
 impl<T: Config> Pallet<T> {
 	/// Create a new instance of balance module
 	pub fn new() -> Self {
-		Self { balances: BTreeMap::new() }
+		Self {
+			storage_version: <Self as OnRuntimeUpgrade>::STORAGE_VERSION,
+			balances: BTreeMap::new(),
+			accounts: BTreeMap::new(),
+			locks: BTreeMap::new(),
+			allowances: BTreeMap::new(),
+			total_issuance: T::Balance::zero(),
+		}
 	}
 
-	/// Set the balance of an account `who` to some `amount`.
+	/// Set the free balance of an account `who` to some `amount`.
+	/// The total issuance follows the change of the free balance, saturating at its bounds.
 	pub fn set_balance(&mut self, who: &T::AccountId, amount: T::Balance) {
-		self.balances.insert(who.clone(), amount);
+		let AccountData { free, reserved } = self.account(who);
+		self.total_issuance = self.total_issuance.saturating_sub(free).saturating_add(amount);
+		self.accounts.insert(who.clone(), AccountData { free: amount, reserved });
+	}
+
+	/// Create `amount` of new balance, credited to the free balance of `who`.
+	pub fn mint(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		self.total_issuance.checked_add(&amount).ok_or("Fund overflow.")?;
+		let free = self.balance(who).checked_add(&amount).ok_or("Fund overflow.")?;
+		self.set_balance(who, free);
+		Ok(())
+	}
+
+	/// Remove up to `amount` from the free balance of `who`, regardless of its locks.
+	/// Returns the amount which was actually removed.
+	pub fn slash(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+		let free = self.balance(who);
+		let slashed = amount.min(free);
+		self.set_balance(who, free.checked_sub(&slashed).unwrap_or(T::Balance::zero()));
+		slashed
+	}
+
+	/// Get the total balance of all the accounts, free and reserved.
+	pub fn total_issuance(&self) -> T::Balance {
+		self.total_issuance
 	}
 
-	/// Get the balance of an account `who`.
+	/// Get the free balance of an account `who`.
 	/// If the account has no stored balance, we return zero.
 	pub fn balance(&self, who: &T::AccountId) -> T::Balance {
-		*self.balances.get(who).unwrap_or(&T::Balance::zero())
+		self.account(who).free
+	}
+
+	/// Get the reserved balance of an account `who`.
+	pub fn reserved_balance(&self, who: &T::AccountId) -> T::Balance {
+		self.account(who).reserved
+	}
+
+	/// Get the free and reserved balance of an account `who`.
+	pub fn account(&self, who: &T::AccountId) -> AccountData<T::Balance> {
+		self.accounts
+			.get(who)
+			.copied()
+			.unwrap_or(AccountData { free: T::Balance::zero(), reserved: T::Balance::zero() })
+	}
+
+	/// Move `amount` from the free balance of `who` to its reserved balance.
+	pub fn reserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let account = self.account(who);
+		let free = account.free.checked_sub(&amount).ok_or("Not enough funds.")?;
+		let reserved = account.reserved.checked_add(&amount).ok_or("Fund overflow.")?;
+		self.ensure_can_withdraw(who, free)?;
+		self.accounts.insert(who.clone(), AccountData { free, reserved });
+		Ok(())
+	}
+
+	/// Move `amount` from the reserved balance of `who` back to its free balance.
+	pub fn unreserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let account = self.account(who);
+		let reserved = account.reserved.checked_sub(&amount).ok_or("Not enough reserved funds.")?;
+		let free = account.free.checked_add(&amount).ok_or("Fund overflow.")?;
+		self.accounts.insert(who.clone(), AccountData { free, reserved });
+		Ok(())
+	}
+
+	/// Move `amount` from the reserved balance of `from` to the free balance of `to`.
+	pub fn repatriate_reserved(
+		&mut self,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		if from == to {
+			return self.unreserve(from, amount)
+		}
+		let from_account = self.account(from);
+		let to_account = self.account(to);
+		let reserved =
+			from_account.reserved.checked_sub(&amount).ok_or("Not enough reserved funds.")?;
+		let free = to_account.free.checked_add(&amount).ok_or("Fund overflow.")?;
+		self.accounts.insert(from.clone(), AccountData { reserved, ..from_account });
+		self.accounts.insert(to.clone(), AccountData { free, ..to_account });
+		Ok(())
+	}
+
+	/// Lock `amount` of the free balance of `who` under the identifier `id`, replacing the
+	/// previous lock with the same identifier, if any.
+	/// Locks overlap: the balance which can not be withdrawn is the largest of the locks, and the
+	/// amount may be above the free balance of the account.
+	pub fn set_lock(&mut self, id: LockIdentifier, who: &T::AccountId, amount: T::Balance) {
+		self.locks.entry(who.clone()).or_default().insert(id, amount);
+	}
+
+	/// Remove the lock with the identifier `id` from the balance of `who`.
+	pub fn remove_lock(&mut self, id: LockIdentifier, who: &T::AccountId) {
+		if let Some(locks) = self.locks.get_mut(who) {
+			locks.remove(&id);
+			if locks.is_empty() {
+				self.locks.remove(who);
+			}
+		}
+	}
+
+	/// Get the part of the free balance of `who` which is locked.
+	pub fn locked_balance(&self, who: &T::AccountId) -> T::Balance {
+		self.locks
+			.get(who)
+			.and_then(|locks| locks.values().max().copied())
+			.unwrap_or(T::Balance::zero())
+	}
+
+	/// Get the part of the free balance of `who` which can be withdrawn.
+	pub fn usable_balance(&self, who: &T::AccountId) -> T::Balance {
+		self.balance(who)
+			.checked_sub(&self.locked_balance(who))
+			.unwrap_or(T::Balance::zero())
+	}
+
+	/// Get the amount `spender` can transfer on behalf of `owner`.
+	pub fn allowance(&self, owner: &T::AccountId, spender: &T::AccountId) -> T::Balance {
+		*self
+			.allowances
+			.get(&(owner.clone(), spender.clone()))
+			.unwrap_or(&T::Balance::zero())
+	}
+
+	/// Transfer `amount` from `from` to `to`.
+	/// This function verifies that `from` has at least `amount` balance to transfer, which is not
+	/// locked, and that no mathematical overflows occur.
+	/// Unlike the `transfer` call, it does not require an origin, so other pallets can move funds
+	/// of the accounts they manage.
+	pub fn do_transfer(
+		&mut self,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let new_from_balance =
+			self.balance(from).checked_sub(&amount).ok_or("Not enough funds.")?;
+		self.ensure_can_withdraw(from, new_from_balance)?;
+		if from == to {
+			return Ok(())
+		}
+		let new_to_balance = self.balance(to).checked_add(&amount).ok_or("Fund overflow.")?;
+
+		self.set_balance(from, new_from_balance);
+		self.set_balance(to, new_to_balance);
+		Ok(())
+	}
+
+	/// Check that the free balance of `who` can be lowered to `new_free`, given its locks.
+	fn ensure_can_withdraw(&self, who: &T::AccountId, new_free: T::Balance) -> DispatchResult {
+		if new_free < self.locked_balance(who) {
+			return Err("Not enough unlocked funds.")
+		}
+		Ok(())
+	}
+
+	/// Sum the free and reserved balance of all the accounts, saturating at the maximum balance.
+	fn sum_accounts(&self) -> T::Balance {
+		self.accounts.values().fold(T::Balance::zero(), |total, account| {
+			total.saturating_add(account.free).saturating_add(account.reserved)
+		})
+	}
+}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {
+	const STORAGE_VERSION: StorageVersion = 2;
+
+	fn on_chain_storage_version(&self) -> StorageVersion {
+		self.storage_version
+	}
+
+	fn on_runtime_upgrade(&mut self) {
+		// Version 1 introduces reserved balances: the free balance of each account moves from
+		// `balances` into its `AccountData`.
+		if self.storage_version < 1 {
+			for (who, free) in std::mem::take(&mut self.balances) {
+				self.accounts.insert(who, AccountData { free, reserved: T::Balance::zero() });
+			}
+			self.storage_version = 1;
+		}
+		// Version 2 introduces the total issuance, computed from the balances of the accounts.
+		if self.storage_version < 2 {
+			self.total_issuance = self.sum_accounts();
+			self.storage_version = 2;
+		}
+	}
+
+	fn post_upgrade(&self) -> DispatchResult {
+		if !self.balances.is_empty() {
+			return Err("Balances of storage version 0 were not migrated")
+		}
+		if self.total_issuance != self.sum_accounts() {
+			return Err("Total issuance does not match the balances of the accounts")
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::system::Config as SystemConfig;
+	use crate::{
+		support::{try_runtime_upgrade, Origin},
+		system::Config as SystemConfig,
+	};
 
 	struct TestConfig;
 
 	impl SystemConfig for TestConfig {
 		type AccountId = String;
+		type RuntimeOrigin = Origin<String>;
+		type RuntimeEvent = ();
 		type BlockNumber = u32;
 		type Nonce = u32;
 	}
@@ -92,7 +376,7 @@ mod tests {
 		let (mut balances, alice, bob) = setup();
 		balances.set_balance(&alice, 100);
 		// Act
-		let result = balances.transfer(alice.clone(), bob.clone(), 75);
+		let result = balances.transfer(Origin::Signed(alice.clone()), bob.clone(), 75);
 		// Assert
 		assert_eq!(result, Ok(()));
 		assert_eq!(balances.balance(&alice), 25);
@@ -105,7 +389,7 @@ mod tests {
 		let (mut balances, alice, bob) = setup();
 		balances.set_balance(&alice, 100);
 		// Act
-		let result = balances.transfer(alice, bob, 110);
+		let result = balances.transfer(Origin::Signed(alice), bob, 110);
 		// Assert
 		assert_eq!(result, Err("Not enough funds."));
 	}
@@ -115,13 +399,299 @@ mod tests {
 		// Arrange
 		let (mut balances, alice, bob) = setup();
 		balances.set_balance(&alice, 100);
-		balances.set_balance(&bob, std::u128::MAX);
+		balances.set_balance(&bob, u128::MAX);
 		// Act
-		let result = balances.transfer(alice, bob, 1);
+		let result = balances.transfer(Origin::Signed(alice), bob, 1);
 		// Assert
 		assert_eq!(result, Err("Fund overflow."));
 	}
 
+	#[test]
+	fn force_set_balance() {
+		// Arrange
+		let (mut balances, alice, bob) = setup();
+		// Act
+		let result = balances.force_set_balance(Origin::Root, alice.clone(), 100);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(
+			balances.force_set_balance(Origin::Signed(bob.clone()), bob.clone(), 100),
+			Err("Origin must be root")
+		);
+		assert_eq!(balances.balance(&bob), 0);
+	}
+
+	#[test]
+	fn transfer_balance_to_self() {
+		// Arrange
+		let (mut balances, alice, _) = setup();
+		balances.set_balance(&alice, 100);
+		// Act
+		let result = balances.transfer(Origin::Signed(alice.clone()), alice.clone(), 60);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(balances.total_issuance(), 100);
+		assert_eq!(
+			balances.transfer(Origin::Signed(alice.clone()), alice.clone(), 110),
+			Err("Not enough funds.")
+		);
+	}
+
+	#[test]
+	fn transfer_balance_unsigned() {
+		// Arrange
+		let (mut balances, alice, bob) = setup();
+		balances.set_balance(&alice, 100);
+		// Act
+		let result = balances.transfer(Origin::None, bob, 10);
+		// Assert
+		assert_eq!(result, Err("Origin must be signed"));
+		assert_eq!(balances.balance(&alice), 100);
+	}
+
+	#[test]
+	fn reserve_balance() {
+		// Arrange
+		let (mut balances, alice, _) = setup();
+		balances.set_balance(&alice, 100);
+		// Act
+		let result = balances.reserve(&alice, 40);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(balances.balance(&alice), 60);
+		assert_eq!(balances.reserved_balance(&alice), 40);
+	}
+
+	#[test]
+	fn reserve_balance_insufficient() {
+		// Arrange
+		let (mut balances, alice, _) = setup();
+		balances.set_balance(&alice, 100);
+		// Act
+		let result = balances.reserve(&alice, 110);
+		// Assert
+		assert_eq!(result, Err("Not enough funds."));
+		assert_eq!(balances.reserved_balance(&alice), 0);
+	}
+
+	#[test]
+	fn unreserve_balance() {
+		// Arrange
+		let (mut balances, alice, _) = setup();
+		balances.set_balance(&alice, 100);
+		let _ = balances.reserve(&alice, 40);
+		// Act
+		let result = balances.unreserve(&alice, 30);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(balances.balance(&alice), 90);
+		assert_eq!(balances.reserved_balance(&alice), 10);
+		assert_eq!(balances.unreserve(&alice, 20), Err("Not enough reserved funds."));
+	}
+
+	#[test]
+	fn transfer_keeps_reserved_balance() {
+		// Arrange
+		let (mut balances, alice, bob) = setup();
+		balances.set_balance(&alice, 100);
+		let _ = balances.reserve(&alice, 40);
+		// Act
+		let result = balances.transfer(Origin::Signed(alice.clone()), bob.clone(), 70);
+		// Assert
+		assert_eq!(result, Err("Not enough funds."));
+		assert_eq!(balances.transfer(Origin::Signed(alice.clone()), bob.clone(), 60), Ok(()));
+		assert_eq!(balances.account(&alice), AccountData { free: 0, reserved: 40 });
+		assert_eq!(balances.total_issuance(), 100);
+	}
+
+	#[test]
+	fn repatriate_reserved_balance() {
+		// Arrange
+		let (mut balances, alice, bob) = setup();
+		balances.set_balance(&alice, 100);
+		balances.set_lock(*b"first   ", &alice, 60);
+		let _ = balances.reserve(&alice, 40);
+		// Act
+		let result = balances.repatriate_reserved(&alice, &bob, 30);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(balances.account(&alice), AccountData { free: 60, reserved: 10 });
+		assert_eq!(balances.account(&bob), AccountData { free: 30, reserved: 0 });
+		assert_eq!(balances.total_issuance(), 100);
+		assert_eq!(
+			balances.repatriate_reserved(&alice, &bob, 20),
+			Err("Not enough reserved funds.")
+		);
+	}
+
+	#[test]
+	fn locked_balance_can_not_be_withdrawn() {
+		// Arrange
+		let (mut balances, alice, bob) = setup();
+		balances.set_balance(&alice, 100);
+		balances.set_lock(*b"first   ", &alice, 30);
+		balances.set_lock(*b"second  ", &alice, 50);
+		// Act
+		let result = balances.transfer(Origin::Signed(alice.clone()), bob.clone(), 60);
+		// Assert
+		assert_eq!(result, Err("Not enough unlocked funds."));
+		assert_eq!(balances.locked_balance(&alice), 50);
+		assert_eq!(balances.usable_balance(&alice), 50);
+		assert_eq!(balances.reserve(&alice, 60), Err("Not enough unlocked funds."));
+		assert_eq!(balances.transfer(Origin::Signed(alice.clone()), bob.clone(), 50), Ok(()));
+	}
+
+	#[test]
+	fn remove_lock() {
+		// Arrange
+		let (mut balances, alice, bob) = setup();
+		balances.set_balance(&alice, 100);
+		balances.set_lock(*b"first   ", &alice, 30);
+		balances.set_lock(*b"second  ", &alice, 50);
+		// Act
+		balances.remove_lock(*b"second  ", &alice);
+		// Assert
+		assert_eq!(balances.locked_balance(&alice), 30);
+		assert_eq!(balances.transfer(Origin::Signed(alice.clone()), bob.clone(), 70), Ok(()));
+		balances.remove_lock(*b"first   ", &alice);
+		assert_eq!(balances.locked_balance(&alice), 0);
+	}
+
+	#[test]
+	fn mint_balance() {
+		// Arrange
+		let (mut balances, alice, _) = setup();
+		balances.set_balance(&alice, 100);
+		// Act
+		let result = balances.mint(&alice, 50);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(balances.balance(&alice), 150);
+		assert_eq!(balances.total_issuance(), 150);
+		assert_eq!(balances.mint(&alice, u128::MAX), Err("Fund overflow."));
+	}
+
+	#[test]
+	fn slash_balance() {
+		// Arrange
+		let (mut balances, alice, _) = setup();
+		balances.set_balance(&alice, 100);
+		balances.set_lock(*b"staking ", &alice, 80);
+		// Act
+		let slashed = balances.slash(&alice, 30);
+		// Assert
+		assert_eq!(slashed, 30);
+		assert_eq!(balances.balance(&alice), 70);
+		assert_eq!(balances.total_issuance(), 70);
+		assert_eq!(balances.slash(&alice, 100), 70);
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.total_issuance(), 0);
+	}
+
+	#[test]
+	fn transfer_from_with_allowance() {
+		// Arrange
+		let (mut balances, alice, bob) = setup();
+		let charlie = String::from("charlie");
+		balances.set_balance(&alice, 100);
+		let _ = balances.approve(Origin::Signed(alice.clone()), bob.clone(), 50);
+		// Act
+		let result =
+			balances.transfer_from(Origin::Signed(bob.clone()), alice.clone(), charlie.clone(), 30);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(balances.balance(&alice), 70);
+		assert_eq!(balances.balance(&charlie), 30);
+		assert_eq!(balances.allowance(&alice, &bob), 20);
+		assert_eq!(balances.allowance(&alice, &charlie), 0);
+	}
+
+	#[test]
+	fn transfer_from_insufficient_allowance() {
+		// Arrange
+		let (mut balances, alice, bob) = setup();
+		balances.set_balance(&alice, 100);
+		let _ = balances.approve(Origin::Signed(alice.clone()), bob.clone(), 50);
+		// Act
+		let result =
+			balances.transfer_from(Origin::Signed(bob.clone()), alice.clone(), bob.clone(), 60);
+		// Assert
+		assert_eq!(result, Err("Not enough allowance."));
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(balances.allowance(&alice, &bob), 50);
+	}
+
+	#[test]
+	fn transfer_from_insufficient_funds() {
+		// Arrange
+		let (mut balances, alice, bob) = setup();
+		balances.set_balance(&alice, 20);
+		let _ = balances.approve(Origin::Signed(alice.clone()), bob.clone(), 50);
+		// Act
+		let result =
+			balances.transfer_from(Origin::Signed(bob.clone()), alice.clone(), bob.clone(), 30);
+		// Assert
+		assert_eq!(result, Err("Not enough funds."));
+		assert_eq!(balances.allowance(&alice, &bob), 50);
+	}
+
+	#[test]
+	fn migrate_v0_to_v1() {
+		// Arrange
+		let (_, alice, bob) = setup();
+		let mut balances = Pallet::<TestConfig> {
+			storage_version: 0,
+			balances: BTreeMap::from([(alice.clone(), 100), (bob.clone(), 50)]),
+			accounts: BTreeMap::new(),
+			locks: BTreeMap::new(),
+			allowances: BTreeMap::new(),
+			total_issuance: 0,
+		};
+		// Act
+		let result = try_runtime_upgrade(&mut balances);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(balances.on_chain_storage_version(), 2);
+		assert_eq!(balances.account(&alice), AccountData { free: 100, reserved: 0 });
+		assert_eq!(balances.account(&bob), AccountData { free: 50, reserved: 0 });
+		assert_eq!(balances.total_issuance(), 150);
+	}
+
+	#[test]
+	fn migrate_v1_to_v2() {
+		// Arrange
+		let (_, alice, bob) = setup();
+		let mut balances = Pallet::<TestConfig> {
+			storage_version: 1,
+			balances: BTreeMap::new(),
+			accounts: BTreeMap::from([
+				(alice.clone(), AccountData { free: 70, reserved: 30 }),
+				(bob.clone(), AccountData { free: 50, reserved: 0 }),
+			]),
+			locks: BTreeMap::new(),
+			allowances: BTreeMap::new(),
+			total_issuance: 0,
+		};
+		// Act
+		let result = try_runtime_upgrade(&mut balances);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(balances.on_chain_storage_version(), 2);
+		assert_eq!(balances.total_issuance(), 150);
+	}
+
+	#[test]
+	fn migrate_up_to_date_storage() {
+		// Arrange
+		let (mut balances, _, _) = setup();
+		// Act
+		let result = try_runtime_upgrade(&mut balances);
+		// Assert
+		assert_eq!(result, Err("Storage is already up to date"));
+	}
+
 	fn setup() -> (
 		Pallet<TestConfig>,
 		<TestConfig as SystemConfig>::AccountId,
@@ -0,0 +1,674 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+	support::{Block, Hash, Header},
+	types, Runtime,
+};
+
+/// The minimal information about an imported block which is needed to choose the best chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockInfo {
+	/// The hash of the block.
+	pub hash: Hash,
+	/// The hash of the parent of the block.
+	pub parent_hash: Hash,
+	/// The number of the block.
+	pub number: types::BlockNumber,
+}
+
+/// A rule to select the best chain among all the branches of the block tree.
+pub trait ForkChoice {
+	/// Returns `true` if the chain ending in `candidate` should replace the chain ending in `best`
+	/// as the best chain.
+	fn is_better(&self, candidate: &BlockInfo, best: &BlockInfo) -> bool;
+}
+
+/// The default fork choice rule: the longest chain is the best chain.
+/// When two chains have the same length, we stay on the chain we have seen first.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LongestChain;
+
+impl ForkChoice for LongestChain {
+	fn is_better(&self, candidate: &BlockInfo, best: &BlockInfo) -> bool {
+		candidate.number > best.number
+	}
+}
+
+/// The outcome of a successful block import.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportResult {
+	/// The block was imported before, nothing changed.
+	AlreadyKnown,
+	/// The block was imported on a branch which is not the best chain.
+	Imported,
+	/// The block was imported on top of the best chain, and is the new best block.
+	NewBest,
+	/// The block made another branch the best chain.
+	/// `retracted` are the blocks removed from the best chain, and `enacted` are the blocks added
+	/// to the best chain, both ordered from the common ancestor upwards.
+	Reorg { retracted: Vec<Hash>, enacted: Vec<Hash> },
+}
+
+/// How much of the historical state the block tree keeps.
+/// Only the state of finalized blocks is ever pruned, since the state of the other blocks may still
+/// be needed to import new blocks or to switch to another branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pruning {
+	/// Keep the state of every block.
+	Archive,
+	/// Keep the state of the finalized block, and of this many finalized blocks before it.
+	Window(types::BlockNumber),
+}
+
+impl Default for Pruning {
+	fn default() -> Self {
+		Pruning::Window(256)
+	}
+}
+
+/// A block stored in the tree, together with the state of the runtime after executing it.
+/// The state is `None` once it has been pruned.
+#[derive(Debug)]
+struct Entry {
+	info: BlockInfo,
+	state: Option<Runtime>,
+}
+
+/// This is the Block Tree.
+/// It imports blocks by their parent hash, keeps all the competing branches, and follows the best
+/// chain according to a [`ForkChoice`] rule.
+///
+/// Every block is executed on top of a copy of the state of its parent when it is imported, and
+/// the resulting state is kept with the block. When the best chain switches to another branch, the
+/// state is reverted to the common ancestor and the blocks of the new branch are enacted, simply by
+/// following the states stored with those blocks.
+///
+/// Once a block is finalized, the branches which do not include it are discarded, and the state of
+/// older finalized blocks is pruned according to the [`Pruning`] mode.
+#[derive(Debug)]
+pub struct BlockTree<F: ForkChoice = LongestChain> {
+	/// All the imported blocks, by hash.
+	blocks: BTreeMap<Hash, Entry>,
+	/// The hash of the genesis block.
+	genesis_hash: Hash,
+	/// The hash of the head of the best chain.
+	best_hash: Hash,
+	/// The hash of the last finalized block.
+	finalized_hash: Hash,
+	/// The rule used to select the best chain.
+	fork_choice: F,
+	/// How much of the historical state we keep.
+	pruning: Pruning,
+}
+
+impl BlockTree<LongestChain> {
+	/// Create a new block tree, starting from the `genesis` state and following the longest chain.
+	pub fn new(genesis: Runtime) -> Self {
+		Self::with_fork_choice(genesis, LongestChain)
+	}
+}
+
+impl<F: ForkChoice> BlockTree<F> {
+	/// Create a new block tree, starting from the `genesis` state and selecting the best chain
+	/// with `fork_choice`.
+	pub fn with_fork_choice(genesis: Runtime, fork_choice: F) -> Self {
+		let genesis_block: types::Block = Block {
+			header: Header {
+				parent_hash: Hash::default(),
+				block_number: genesis.system.block_number(),
+			},
+			extrinsics: Vec::new(),
+		};
+		let info = BlockInfo {
+			hash: genesis_block.hash(),
+			parent_hash: genesis_block.header.parent_hash,
+			number: genesis_block.header.block_number,
+		};
+		let genesis_hash = info.hash;
+
+		let mut blocks = BTreeMap::new();
+		blocks.insert(genesis_hash, Entry { info, state: Some(genesis) });
+
+		Self {
+			blocks,
+			genesis_hash,
+			best_hash: genesis_hash,
+			finalized_hash: genesis_hash,
+			fork_choice,
+			pruning: Pruning::default(),
+		}
+	}
+
+	/// Use the given `pruning` mode for the historical state.
+	pub fn with_pruning(mut self, pruning: Pruning) -> Self {
+		self.pruning = pruning;
+		self
+	}
+
+	/// Import a `block` on top of its parent, which must already be in the tree.
+	/// The block is executed on a copy of the state of its parent, and becomes the new best block
+	/// if the fork choice rule prefers it over the current best block.
+	pub fn import(&mut self, block: types::Block) -> Result<ImportResult, &'static str> {
+		let hash = block.hash();
+		if self.blocks.contains_key(&hash) {
+			return Ok(ImportResult::AlreadyKnown)
+		}
+
+		if block.header.block_number <= self.finalized_number() {
+			return Err("Block is not above the finalized block")
+		}
+
+		let parent = self.blocks.get(&block.header.parent_hash).ok_or("Unknown parent block")?;
+		let parent_state = parent.state.as_ref().ok_or("State of the parent block is pruned")?;
+		let info = BlockInfo {
+			hash,
+			parent_hash: block.header.parent_hash,
+			number: block.header.block_number,
+		};
+
+		let mut state = parent_state.clone();
+		state.execute(block)?;
+
+		let is_better = self.fork_choice.is_better(&info, &self.blocks[&self.best_hash].info);
+		self.blocks.insert(hash, Entry { info: info.clone(), state: Some(state) });
+
+		if !is_better {
+			return Ok(ImportResult::Imported)
+		}
+
+		let previous_best = std::mem::replace(&mut self.best_hash, hash);
+		if info.parent_hash == previous_best {
+			return Ok(ImportResult::NewBest)
+		}
+
+		let (retracted, enacted) = self.tree_route(previous_best, hash);
+		Ok(ImportResult::Reorg { retracted, enacted })
+	}
+
+	/// The hash of the genesis block.
+	pub fn genesis_hash(&self) -> Hash {
+		self.genesis_hash
+	}
+
+	/// The hash of the best block.
+	pub fn best_hash(&self) -> Hash {
+		self.best_hash
+	}
+
+	/// The number of the best block.
+	pub fn best_number(&self) -> types::BlockNumber {
+		self.blocks[&self.best_hash].info.number
+	}
+
+	/// The state of the runtime at the best block.
+	pub fn best_state(&self) -> &Runtime {
+		self.blocks[&self.best_hash]
+			.state
+			.as_ref()
+			.expect("the best block is never pruned")
+	}
+
+	/// The hash of the block with the given `number` on the best chain, if any.
+	pub fn canonical_hash(&self, number: types::BlockNumber) -> Option<Hash> {
+		let mut info = &self.blocks[&self.best_hash].info;
+		while info.number > number {
+			info = &self.blocks[&info.parent_hash].info;
+		}
+		(info.number == number).then_some(info.hash)
+	}
+
+	/// The state of the runtime after the block `hash`.
+	/// Fails if the block is unknown, or if its state was pruned.
+	pub fn state_at_hash(&self, hash: &Hash) -> Result<&Runtime, &'static str> {
+		let entry = self.blocks.get(hash).ok_or("Unknown block")?;
+		entry.state.as_ref().ok_or("State of the block is pruned")
+	}
+
+	/// The state of the runtime after the block `number` of the best chain.
+	/// This is used to answer pallet queries at a historical block, for example:
+	/// `block_tree.state_at(42)?.balances.balance(&alice)`.
+	pub fn state_at(&self, number: types::BlockNumber) -> Result<&Runtime, &'static str> {
+		let hash = self.canonical_hash(number).ok_or("Unknown block")?;
+		self.state_at_hash(&hash)
+	}
+
+	/// The hash of the last finalized block.
+	pub fn finalized_hash(&self) -> Hash {
+		self.finalized_hash
+	}
+
+	/// The number of the last finalized block.
+	pub fn finalized_number(&self) -> types::BlockNumber {
+		self.blocks[&self.finalized_hash].info.number
+	}
+
+	/// Finalize the block `hash`, which must be on the best chain.
+	/// All the branches which do not include this block are discarded, and the state of the older
+	/// finalized blocks is pruned according to the pruning mode.
+	pub fn finalize(&mut self, hash: Hash) -> Result<(), &'static str> {
+		let number = self.blocks.get(&hash).ok_or("Unknown block")?.info.number;
+		if number <= self.finalized_number() {
+			return Err("Block is already finalized")
+		}
+
+		let canonical = self.ancestors(self.best_hash);
+		if !canonical.contains(&hash) {
+			return Err("Block is not on the best chain")
+		}
+		self.finalized_hash = hash;
+
+		// Keep the finalized chain, and the blocks built on top of the finalized block.
+		let finalized_chain = self.ancestors(hash);
+		let discarded = self
+			.blocks
+			.values()
+			.map(|entry| &entry.info)
+			.filter(|info| {
+				if info.number <= number {
+					!finalized_chain.contains(&info.hash)
+				} else {
+					!self.ancestors(info.hash).contains(&hash)
+				}
+			})
+			.map(|info| info.hash)
+			.collect::<Vec<_>>();
+		for hash in discarded {
+			self.blocks.remove(&hash);
+		}
+
+		// Record the finality in the state of the finalized block and of its descendants.
+		for entry in self.blocks.values_mut().filter(|entry| entry.info.number >= number) {
+			if let Some(state) = entry.state.as_mut() {
+				state.system.set_finalized_number(number);
+			}
+		}
+
+		if let Pruning::Window(window) = self.pruning {
+			for entry in self.blocks.values_mut() {
+				if entry.info.number.saturating_add(window) < number {
+					entry.state = None;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Get the information about an imported block, if any.
+	pub fn block_info(&self, hash: &Hash) -> Option<&BlockInfo> {
+		self.blocks.get(hash).map(|entry| &entry.info)
+	}
+
+	/// All the ancestors of the block `hash`, including the block itself.
+	fn ancestors(&self, mut hash: Hash) -> BTreeSet<Hash> {
+		let mut ancestors = BTreeSet::new();
+		while let Some(entry) = self.blocks.get(&hash) {
+			ancestors.insert(hash);
+			hash = entry.info.parent_hash;
+		}
+		ancestors
+	}
+
+	/// Compute the route between the blocks `from` and `to`, going through their common ancestor.
+	/// Returns the blocks retracted from `from` and enacted towards `to`, both excluding the common
+	/// ancestor and ordered from the common ancestor upwards.
+	fn tree_route(&self, from: Hash, to: Hash) -> (Vec<Hash>, Vec<Hash>) {
+		let mut from = &self.blocks[&from].info;
+		let mut to = &self.blocks[&to].info;
+		let mut retracted = Vec::new();
+		let mut enacted = Vec::new();
+
+		while from.number > to.number {
+			retracted.push(from.hash);
+			from = &self.blocks[&from.parent_hash].info;
+		}
+		while to.number > from.number {
+			enacted.push(to.hash);
+			to = &self.blocks[&to.parent_hash].info;
+		}
+		while from.hash != to.hash {
+			retracted.push(from.hash);
+			enacted.push(to.hash);
+			from = &self.blocks[&from.parent_hash].info;
+			to = &self.blocks[&to.parent_hash].info;
+		}
+
+		retracted.reverse();
+		enacted.reverse();
+		(retracted, enacted)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{balances, support::Extrinsic, timestamp, RuntimeCall};
+
+	#[test]
+	fn import_extends_best_chain() {
+		// Arrange
+		let mut tree = setup();
+		let block_1 = transfer_block(tree.genesis_hash(), 1, "bob", 10);
+		let hash_1 = block_1.hash();
+
+		// Act
+		let res = tree.import(block_1);
+
+		// Assert
+		assert_eq!(res, Ok(ImportResult::NewBest));
+		assert_eq!(tree.best_hash(), hash_1);
+		assert_eq!(tree.best_number(), 1);
+		assert_eq!(tree.best_state().balances.balance(&"bob".to_string()), 10);
+	}
+
+	#[test]
+	fn import_known_block() {
+		// Arrange
+		let mut tree = setup();
+		let block_1 = transfer_block(tree.genesis_hash(), 1, "bob", 10);
+		let _ = tree.import(block_1.clone());
+
+		// Act
+		let res = tree.import(block_1);
+
+		// Assert
+		assert_eq!(res, Ok(ImportResult::AlreadyKnown));
+	}
+
+	#[test]
+	fn import_fails_on_unknown_parent() {
+		// Arrange
+		let mut tree = setup();
+		let block = transfer_block(42, 1, "bob", 10);
+
+		// Act
+		let res = tree.import(block);
+
+		// Assert
+		assert_eq!(res, Err("Unknown parent block"));
+	}
+
+	#[test]
+	fn import_fails_on_wrong_block_number() {
+		// Arrange
+		let mut tree = setup();
+		let block = transfer_block(tree.genesis_hash(), 2, "bob", 10);
+
+		// Act
+		let res = tree.import(block);
+
+		// Assert
+		assert_eq!(res, Err("block number does not match what is expected"));
+		assert_eq!(tree.best_hash(), tree.genesis_hash());
+	}
+
+	#[test]
+	fn import_side_branch_keeps_best_chain() {
+		// Arrange
+		let mut tree = setup();
+		let block_a1 = transfer_block(tree.genesis_hash(), 1, "bob", 10);
+		let hash_a1 = block_a1.hash();
+		let block_b1 = transfer_block(tree.genesis_hash(), 1, "charlie", 20);
+		let _ = tree.import(block_a1);
+
+		// Act
+		let res = tree.import(block_b1);
+
+		// Assert
+		assert_eq!(res, Ok(ImportResult::Imported));
+		assert_eq!(tree.best_hash(), hash_a1);
+		assert_eq!(tree.best_state().balances.balance(&"charlie".to_string()), 0);
+	}
+
+	#[test]
+	fn import_longer_branch_reorgs() {
+		// Arrange
+		let mut tree = setup();
+		let block_a1 = transfer_block(tree.genesis_hash(), 1, "bob", 10);
+		let hash_a1 = block_a1.hash();
+		let block_b1 = transfer_block(tree.genesis_hash(), 1, "charlie", 20);
+		let hash_b1 = block_b1.hash();
+		let block_b2 = transfer_block(hash_b1, 2, "charlie", 5);
+		let hash_b2 = block_b2.hash();
+		let _ = tree.import(block_a1);
+		let _ = tree.import(block_b1);
+
+		// Act
+		let res = tree.import(block_b2);
+
+		// Assert
+		assert_eq!(
+			res,
+			Ok(ImportResult::Reorg { retracted: vec![hash_a1], enacted: vec![hash_b1, hash_b2] })
+		);
+		assert_eq!(tree.best_hash(), hash_b2);
+		let state = tree.best_state();
+		assert_eq!(state.system.block_number(), 2);
+		assert_eq!(state.balances.balance(&"alice".to_string()), 75);
+		assert_eq!(state.balances.balance(&"bob".to_string()), 0);
+		assert_eq!(state.balances.balance(&"charlie".to_string()), 25);
+	}
+
+	#[test]
+	fn custom_fork_choice() {
+		// Arrange
+		struct FirstSeen;
+		impl ForkChoice for FirstSeen {
+			fn is_better(&self, candidate: &BlockInfo, best: &BlockInfo) -> bool {
+				candidate.parent_hash == best.hash
+			}
+		}
+		let mut tree = BlockTree::with_fork_choice(genesis(), FirstSeen);
+		let block_a1 = transfer_block(tree.genesis_hash(), 1, "bob", 10);
+		let hash_a1 = block_a1.hash();
+		let block_b1 = transfer_block(tree.genesis_hash(), 1, "charlie", 20);
+		let block_b2 = transfer_block(block_b1.hash(), 2, "charlie", 5);
+		let _ = tree.import(block_a1);
+		let _ = tree.import(block_b1);
+
+		// Act
+		let res = tree.import(block_b2);
+
+		// Assert
+		assert_eq!(res, Ok(ImportResult::Imported));
+		assert_eq!(tree.best_hash(), hash_a1);
+	}
+
+	#[test]
+	fn finalize_discards_other_branches() {
+		// Arrange
+		let mut tree = setup();
+		let block_a1 = transfer_block(tree.genesis_hash(), 1, "bob", 10);
+		let hash_a1 = block_a1.hash();
+		let block_a2 = transfer_block(hash_a1, 2, "bob", 10);
+		let hash_a2 = block_a2.hash();
+		let block_b1 = transfer_block(tree.genesis_hash(), 1, "charlie", 20);
+		let hash_b1 = block_b1.hash();
+		let block_c2 = transfer_block(hash_a1, 2, "charlie", 20);
+		let hash_c2 = block_c2.hash();
+		for block in [block_a1, block_a2, block_b1, block_c2] {
+			let _ = tree.import(block);
+		}
+
+		// Act
+		let res = tree.finalize(hash_a1);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(tree.finalized_hash(), hash_a1);
+		assert_eq!(tree.finalized_number(), 1);
+		assert_eq!(tree.best_state().system.finalized_number(), 1);
+		assert!(tree.block_info(&hash_b1).is_none());
+		assert!(tree.block_info(&hash_a2).is_some());
+		assert!(tree.block_info(&hash_c2).is_some());
+	}
+
+	#[test]
+	fn finalize_errors() {
+		// Arrange
+		let mut tree = setup();
+		let block_a1 = transfer_block(tree.genesis_hash(), 1, "bob", 10);
+		let hash_a1 = block_a1.hash();
+		let block_b1 = transfer_block(tree.genesis_hash(), 1, "charlie", 20);
+		let hash_b1 = block_b1.hash();
+		let _ = tree.import(block_a1);
+		let _ = tree.import(block_b1);
+
+		// Act & Assert
+		assert_eq!(tree.finalize(42), Err("Unknown block"));
+		assert_eq!(tree.finalize(hash_b1), Err("Block is not on the best chain"));
+		assert_eq!(tree.finalize(tree.genesis_hash()), Err("Block is already finalized"));
+		assert_eq!(tree.finalize(hash_a1), Ok(()));
+		assert_eq!(tree.finalize(hash_a1), Err("Block is already finalized"));
+	}
+
+	#[test]
+	fn import_below_finalized_block_fails() {
+		// Arrange
+		let mut tree = setup();
+		let block_a1 = transfer_block(tree.genesis_hash(), 1, "bob", 10);
+		let hash_a1 = block_a1.hash();
+		let _ = tree.import(block_a1);
+		let _ = tree.finalize(hash_a1);
+		let block_b1 = transfer_block(tree.genesis_hash(), 1, "charlie", 20);
+
+		// Act
+		let res = tree.import(block_b1);
+
+		// Assert
+		assert_eq!(res, Err("Block is not above the finalized block"));
+	}
+
+	#[test]
+	fn finalize_prunes_old_state() {
+		// Arrange
+		let mut tree = setup().with_pruning(Pruning::Window(1));
+		let hashes = import_chain(&mut tree, 3);
+
+		// Act
+		let res = tree.finalize(hashes[2]);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert!(tree.blocks[&tree.genesis_hash()].state.is_none());
+		assert!(tree.blocks[&hashes[0]].state.is_none());
+		assert!(tree.blocks[&hashes[1]].state.is_some());
+		assert!(tree.blocks[&hashes[2]].state.is_some());
+	}
+
+	#[test]
+	fn archive_keeps_all_state() {
+		// Arrange
+		let mut tree = setup().with_pruning(Pruning::Archive);
+		let hashes = import_chain(&mut tree, 3);
+
+		// Act
+		let res = tree.finalize(hashes[2]);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert!(tree.blocks.values().all(|entry| entry.state.is_some()));
+	}
+
+	#[test]
+	fn query_state_at_historical_blocks() {
+		// Arrange
+		let mut tree = setup();
+		import_chain(&mut tree, 3);
+		let bob = "bob".to_string();
+
+		// Act
+		let balances = (0..=3)
+			.map(|number| tree.state_at(number).map(|state| state.balances.balance(&bob)))
+			.collect::<Vec<_>>();
+
+		// Assert
+		assert_eq!(balances, vec![Ok(0), Ok(1), Ok(2), Ok(3)]);
+		assert_eq!(tree.state_at(4).map(|_| ()), Err("Unknown block"));
+	}
+
+	#[test]
+	fn query_state_follows_best_chain() {
+		// Arrange
+		let mut tree = setup();
+		let block_a1 = transfer_block(tree.genesis_hash(), 1, "bob", 10);
+		let hash_a1 = block_a1.hash();
+		let block_b1 = transfer_block(tree.genesis_hash(), 1, "charlie", 20);
+		let hash_b1 = block_b1.hash();
+		let _ = tree.import(block_a1);
+		let _ = tree.import(block_b1);
+		let charlie = "charlie".to_string();
+
+		// Act
+		let canonical = tree.state_at(1).map(|state| state.balances.balance(&charlie));
+		let side = tree.state_at_hash(&hash_b1).map(|state| state.balances.balance(&charlie));
+
+		// Assert
+		assert_eq!(tree.canonical_hash(1), Some(hash_a1));
+		assert_eq!(canonical, Ok(0));
+		assert_eq!(side, Ok(20));
+	}
+
+	#[test]
+	fn query_pruned_state_fails() {
+		// Arrange
+		let mut tree = setup().with_pruning(Pruning::Window(1));
+		let hashes = import_chain(&mut tree, 3);
+		let _ = tree.finalize(hashes[2]);
+
+		// Act
+		let res = tree.state_at(1).map(|_| ());
+
+		// Assert
+		assert_eq!(res, Err("State of the block is pruned"));
+		assert!(tree.state_at(2).is_ok());
+	}
+
+	fn import_chain(tree: &mut BlockTree, length: types::BlockNumber) -> Vec<Hash> {
+		let mut hashes = Vec::new();
+		let mut parent_hash = tree.genesis_hash();
+		for block_number in 1..=length {
+			let block = transfer_block(parent_hash, block_number, "bob", 1);
+			parent_hash = block.hash();
+			hashes.push(parent_hash);
+			let _ = tree.import(block);
+		}
+		hashes
+	}
+
+	fn genesis() -> Runtime {
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&"alice".to_string(), 100);
+		runtime
+	}
+
+	fn setup() -> BlockTree {
+		BlockTree::new(genesis())
+	}
+
+	fn transfer_block(
+		parent_hash: Hash,
+		block_number: types::BlockNumber,
+		to: &str,
+		amount: types::Balance,
+	) -> types::Block {
+		Block {
+			header: Header { parent_hash, block_number },
+			extrinsics: vec![
+				Extrinsic {
+					caller: None,
+					call: RuntimeCall::timestamp(timestamp::Call::set {
+						now: types::Moment::from(block_number) * 1_000,
+					}),
+				},
+				Extrinsic {
+					caller: Some("alice".to_string()),
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: to.to_string(),
+						amount,
+					}),
+				},
+			],
+		}
+	}
+}
@@ -0,0 +1,471 @@
+use std::collections::BTreeMap;
+
+use num::{CheckedAdd, CheckedSub, Zero};
+
+use crate::{
+	balances::{self, LockIdentifier},
+	support::{
+		Dispatch, DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, Origin, ProvideInherent,
+		ValidateUnsigned,
+	},
+	system::{self, ensure_signed},
+};
+
+/// The identifier of the lock of the balance used to vote.
+pub const DEMOCRACY_ID: LockIdentifier = *b"democrac";
+
+/// The index of a proposal.
+pub type PropIndex = u32;
+
+pub trait Config:
+	balances::Config
+	+ system::Config<
+		BlockNumber: CheckedAdd + Ord,
+		RuntimeEvent: From<
+			Event<<Self as system::Config>::AccountId, <Self as system::Config>::BlockNumber>,
+		>,
+	> + Sized
+{
+	/// The number of blocks during which a proposal can be voted on.
+	const VOTING_PERIOD: Self::BlockNumber;
+	/// The number of blocks between the end of the vote on a passing proposal and its dispatch.
+	const ENACTMENT_DELAY: Self::BlockNumber;
+	/// The type of the calls which can be proposed.
+	type RuntimeCall: Clone;
+}
+
+/// A proposal being voted on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Referendum<AccountId, BlockNumber, Balance, Call> {
+	/// The account which submitted the proposal.
+	pub proposer: AccountId,
+	/// The call dispatched with the root origin if the proposal passes.
+	pub call: Call,
+	/// The block at which the vote ends.
+	pub end: BlockNumber,
+	/// The balance voting for the proposal.
+	pub ayes: Balance,
+	/// The balance voting against the proposal.
+	pub nays: Balance,
+}
+
+/// The vote of an account on a proposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Vote<Balance> {
+	/// Whether the vote is for the proposal.
+	pub aye: bool,
+	/// The balance of the voter, which weights the vote.
+	pub balance: Balance,
+}
+
+/// The events deposited by the Democracy Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId, BlockNumber> {
+	/// `proposer` submitted the proposal `index`, which can be voted on until the block `end`.
+	Proposed { index: PropIndex, proposer: AccountId, end: BlockNumber },
+	/// `voter` voted on the proposal `index`.
+	Voted { index: PropIndex, voter: AccountId, aye: bool },
+	/// The proposal `index` passed, and will be dispatched at the block `enactment`.
+	Passed { index: PropIndex, enactment: BlockNumber },
+	/// The proposal `index` did not pass.
+	NotPassed { index: PropIndex },
+	/// The call of the proposal `index` was dispatched, with the given result.
+	Enacted { index: PropIndex, result: DispatchResult },
+}
+
+/// This is the Democracy Module.
+/// It allows accounts to submit proposals, and to vote on them with their balance, which stays
+/// locked until the vote ends. A proposal passes if more balance votes for it than against it, in
+/// which case its call is dispatched with the root origin after `ENACTMENT_DELAY` blocks.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The proposals being voted on.
+	#[allow(clippy::type_complexity)]
+	referenda:
+		BTreeMap<PropIndex, Referendum<T::AccountId, T::BlockNumber, T::Balance, T::RuntimeCall>>,
+	/// The votes of each account on each proposal.
+	votes: BTreeMap<(PropIndex, T::AccountId), Vote<T::Balance>>,
+	/// The calls of the passed proposals, by the block at which they are dispatched.
+	enactments: BTreeMap<T::BlockNumber, Vec<(PropIndex, T::RuntimeCall)>>,
+	/// The index of the next proposal.
+	next_index: PropIndex,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+{
+	/// Submit a proposal to dispatch `call` with the root origin.
+	#[allow(clippy::boxed_local)]
+	pub fn propose(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let proposer = ensure_signed(origin)?;
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		let end = system
+			.block_number()
+			.checked_add(&T::VOTING_PERIOD)
+			.ok_or("Block number overflow")?;
+
+		let democracy: &mut Self = runtime.pallet_mut();
+		let index = democracy.next_index;
+		democracy.next_index = index.checked_add(1).ok_or("Proposal index overflow")?;
+		let referendum = Referendum {
+			proposer: proposer.clone(),
+			call: *call,
+			end,
+			ayes: T::Balance::zero(),
+			nays: T::Balance::zero(),
+		};
+		democracy.referenda.insert(index, referendum);
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::Proposed { index, proposer, end });
+		Ok(())
+	}
+
+	/// Vote for or against the proposal `index`, with the whole balance of the caller, which stays
+	/// locked until the vote ends. A previous vote of the caller on the proposal is replaced.
+	pub fn vote(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		index: PropIndex,
+		aye: bool,
+	) -> DispatchResult {
+		let voter = ensure_signed(origin)?;
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		let now = system.block_number();
+		let balances: &balances::Pallet<T> = runtime.pallet();
+		let balance = balances.balance(&voter);
+		if balance.is_zero() {
+			return Err("No balance to vote with")
+		}
+
+		let democracy: &mut Self = runtime.pallet_mut();
+		let referendum = democracy.referenda.get_mut(&index).ok_or("Proposal not found")?;
+		if now >= referendum.end {
+			return Err("Voting period has ended")
+		}
+		let vote = Vote { aye, balance };
+		if let Some(previous) = democracy.votes.insert((index, voter.clone()), vote) {
+			let tally = if previous.aye { &mut referendum.ayes } else { &mut referendum.nays };
+			*tally = tally.checked_sub(&previous.balance).unwrap_or(T::Balance::zero());
+		}
+		let tally = if aye { &mut referendum.ayes } else { &mut referendum.nays };
+		*tally = tally.checked_add(&balance).ok_or("Fund overflow.")?;
+
+		Self::update_lock(runtime, &voter);
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::Voted { index, voter, aye });
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Democracy Pallet.
+	pub fn new() -> Self {
+		Self {
+			referenda: BTreeMap::new(),
+			votes: BTreeMap::new(),
+			enactments: BTreeMap::new(),
+			next_index: 0,
+		}
+	}
+
+	/// Get the proposal `index`, if it is being voted on.
+	#[allow(clippy::type_complexity)]
+	pub fn referendum(
+		&self,
+		index: PropIndex,
+	) -> Option<&Referendum<T::AccountId, T::BlockNumber, T::Balance, T::RuntimeCall>> {
+		self.referenda.get(&index)
+	}
+
+	/// Get the vote of `who` on the proposal `index`, if any.
+	pub fn vote_of(&self, index: PropIndex, who: &T::AccountId) -> Option<&Vote<T::Balance>> {
+		self.votes.get(&(index, who.clone()))
+	}
+
+	/// Lock the largest balance `who` votes with, or remove the lock if it has no vote left.
+	fn update_lock(runtime: &mut T, who: &T::AccountId)
+	where
+		T: GetPallet<Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		let democracy: &Self = runtime.pallet();
+		let locked = democracy
+			.votes
+			.iter()
+			.filter(|((_, voter), _)| voter == who)
+			.map(|(_, vote)| vote.balance)
+			.max();
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		match locked {
+			Some(amount) => balances.set_lock(DEMOCRACY_ID, who, amount),
+			None => balances.remove_lock(DEMOCRACY_ID, who),
+		}
+	}
+
+	/// End the vote on the proposal `index` at the block `n`, unlocking the balance of its voters,
+	/// and schedule its call if it passed.
+	fn close(runtime: &mut T, index: PropIndex, n: T::BlockNumber)
+	where
+		T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		let democracy: &mut Self = runtime.pallet_mut();
+		let Some(referendum) = democracy.referenda.remove(&index) else { return };
+		let voters = democracy
+			.votes
+			.keys()
+			.filter(|(vote_index, _)| *vote_index == index)
+			.map(|(_, voter)| voter.clone())
+			.collect::<Vec<_>>();
+		for voter in voters {
+			let democracy: &mut Self = runtime.pallet_mut();
+			democracy.votes.remove(&(index, voter.clone()));
+			Self::update_lock(runtime, &voter);
+		}
+
+		let enactment = n.checked_add(&T::ENACTMENT_DELAY);
+		let event = match enactment {
+			Some(enactment) if referendum.ayes > referendum.nays => {
+				let democracy: &mut Self = runtime.pallet_mut();
+				democracy
+					.enactments
+					.entry(enactment)
+					.or_default()
+					.push((index, referendum.call));
+				Event::Passed { index, enactment }
+			},
+			_ => Event::NotPassed { index },
+		};
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(event);
+	}
+}
+
+impl<T> Hooks<T, T::BlockNumber> for Pallet<T>
+where
+	T: Config
+		+ Dispatch<Origin = T::RuntimeOrigin, Call = T::RuntimeCall>
+		+ GetPallet<Pallet<T>>
+		+ GetPallet<system::Pallet<T>>
+		+ GetPallet<balances::Pallet<T>>,
+{
+	/// End the votes on the proposals whose voting period is over, and dispatch the calls of the
+	/// passed proposals due at the block `n`.
+	fn on_initialize(runtime: &mut T, n: T::BlockNumber) {
+		let democracy: &Self = runtime.pallet();
+		let ended = democracy
+			.referenda
+			.iter()
+			.filter(|(_, referendum)| referendum.end <= n)
+			.map(|(index, _)| *index)
+			.collect::<Vec<_>>();
+		for index in ended {
+			Self::close(runtime, index, n);
+		}
+
+		let democracy: &mut Self = runtime.pallet_mut();
+		for (index, call) in democracy.enactments.remove(&n).unwrap_or_default() {
+			let result = runtime.dispatch(Origin::Root.into(), call);
+
+			let system: &mut system::Pallet<T> = runtime.pallet_mut();
+			system.deposit_event(Event::Enacted { index, result });
+		}
+	}
+}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		balances, sudo,
+		support::{Extrinsic, Header},
+		timestamp, types, Runtime, RuntimeCall, RuntimeEvent,
+	};
+
+	#[test]
+	fn passed_proposal_is_enacted() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::propose(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			Box::new(force_set_balance("charlie", 500)),
+		);
+		let _ = Pallet::vote(&mut runtime, Origin::Signed(alice.clone()), 0, true);
+		let _ = Pallet::vote(&mut runtime, Origin::Signed(bob.clone()), 0, false);
+
+		// Act
+		execute_blocks(&mut runtime, 10);
+		let charlie = String::from("charlie");
+		let charlie_at_block_10 = runtime.balances.balance(&charlie);
+		let events_at_block_10 = democracy_events(&runtime);
+		execute_blocks(&mut runtime, 5);
+
+		// Assert
+		assert_eq!(charlie_at_block_10, 0);
+		assert_eq!(events_at_block_10, vec![Event::Passed { index: 0, enactment: 15 }]);
+		assert_eq!(runtime.balances.balance(&charlie), 500);
+		assert_eq!(runtime.democracy.referendum(0), None);
+		assert_eq!(runtime.balances.locked_balance(&alice), 0);
+		assert_eq!(democracy_events(&runtime), vec![Event::Enacted { index: 0, result: Ok(()) }]);
+	}
+
+	#[test]
+	fn passed_proposal_replaces_sudo_key() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		runtime.sudo.initialize_key(&alice);
+		let call = RuntimeCall::sudo(sudo::Call::set_key { new: bob.clone() });
+		let _ = Pallet::propose(&mut runtime, Origin::Signed(bob.clone()), Box::new(call));
+		let _ = Pallet::vote(&mut runtime, Origin::Signed(alice.clone()), 0, true);
+
+		// Act
+		execute_blocks(&mut runtime, 15);
+
+		// Assert
+		assert_eq!(runtime.sudo.key(), Some(&bob));
+		assert_eq!(democracy_events(&runtime), vec![Event::Enacted { index: 0, result: Ok(()) }]);
+	}
+
+	#[test]
+	fn rejected_proposal_is_not_enacted() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::propose(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			Box::new(force_set_balance("charlie", 500)),
+		);
+		let _ = Pallet::vote(&mut runtime, Origin::Signed(alice.clone()), 0, false);
+		let _ = Pallet::vote(&mut runtime, Origin::Signed(bob.clone()), 0, true);
+
+		// Act
+		execute_blocks(&mut runtime, 10);
+		let events_at_block_10 = democracy_events(&runtime);
+		execute_blocks(&mut runtime, 5);
+
+		// Assert
+		assert_eq!(events_at_block_10, vec![Event::NotPassed { index: 0 }]);
+		assert_eq!(runtime.balances.balance(&String::from("charlie")), 0);
+		assert!(democracy_events(&runtime).is_empty());
+	}
+
+	#[test]
+	fn vote_locks_balance_and_can_be_changed() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::propose(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			Box::new(force_set_balance("charlie", 500)),
+		);
+
+		// Act
+		let first = Pallet::vote(&mut runtime, Origin::Signed(alice.clone()), 0, true);
+		let second = Pallet::vote(&mut runtime, Origin::Signed(alice.clone()), 0, false);
+
+		// Assert
+		assert_eq!(first, Ok(()));
+		assert_eq!(second, Ok(()));
+		assert_eq!(runtime.democracy.vote_of(0, &alice), Some(&Vote { aye: false, balance: 100 }));
+		let referendum = runtime.democracy.referendum(0).expect("the proposal is being voted on");
+		assert_eq!((referendum.ayes, referendum.nays), (0, 100));
+		assert_eq!(runtime.balances.locked_balance(&alice), 100);
+		assert_eq!(
+			runtime.balances.transfer(Origin::Signed(alice), bob, 10),
+			Err("Not enough unlocked funds.")
+		);
+	}
+
+	#[test]
+	fn vote_errors() {
+		// Arrange
+		let (mut runtime, alice, _) = setup();
+		let _ = Pallet::propose(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			Box::new(force_set_balance("charlie", 500)),
+		);
+
+		// Act & Assert
+		assert_eq!(
+			Pallet::vote(&mut runtime, Origin::Signed(alice.clone()), 1, true),
+			Err("Proposal not found")
+		);
+		assert_eq!(
+			Pallet::vote(&mut runtime, Origin::Signed(String::from("charlie")), 0, true),
+			Err("No balance to vote with")
+		);
+		for _ in 0..10 {
+			runtime.system.inc_block_number();
+		}
+		assert_eq!(
+			Pallet::vote(&mut runtime, Origin::Signed(alice), 0, true),
+			Err("Voting period has ended")
+		);
+	}
+
+	fn force_set_balance(who: &str, amount: u128) -> RuntimeCall {
+		RuntimeCall::balances(balances::Call::force_set_balance { who: who.to_string(), amount })
+	}
+
+	fn democracy_events(runtime: &Runtime) -> Vec<Event<String, u32>> {
+		runtime
+			.system
+			.events()
+			.iter()
+			.filter_map(|event| match event {
+				RuntimeEvent::democracy(event) => Some(event.clone()),
+				_ => None,
+			})
+			.collect()
+	}
+
+	fn execute_blocks(runtime: &mut Runtime, count: u32) {
+		for _ in 0..count {
+			runtime.system.reset_events();
+			let block_number = runtime.system.block_number() + 1;
+			let block = types::Block {
+				header: Header { parent_hash: 0, block_number },
+				extrinsics: vec![Extrinsic {
+					caller: None,
+					call: RuntimeCall::timestamp(timestamp::Call::set {
+						now: types::Moment::from(block_number) * 1_000,
+					}),
+				}],
+			};
+			runtime.execute_block(block).expect("empty blocks are valid");
+		}
+	}
+
+	fn setup() -> (Runtime, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&alice, 100);
+		runtime.balances.set_balance(&bob, 50);
+
+		(runtime, alice, bob)
+	}
+}
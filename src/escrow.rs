@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	balances,
+	support::{
+		DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, ProvideInherent, ValidateUnsigned,
+	},
+	system::{self, ensure_signed},
+};
+
+/// The identifier of an escrow.
+pub type EscrowId = u32;
+
+pub trait Config:
+	balances::Config
+	+ system::Config<
+		BlockNumber: Ord,
+		RuntimeEvent: From<
+			Event<<Self as system::Config>::AccountId, <Self as balances::Config>::Balance>,
+		>,
+	>
+{
+}
+
+/// A payment held in escrow, until it is released to the payee or returned to the payer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Escrow<AccountId, Balance, BlockNumber> {
+	/// The account which pays, and whose balance is reserved.
+	pub payer: AccountId,
+	/// The account which is paid when the escrow is released.
+	pub payee: AccountId,
+	/// The account which can release the escrow on behalf of the payer.
+	pub arbiter: AccountId,
+	/// The balance reserved from the payer.
+	pub amount: Balance,
+	/// The block at which the balance returns to the payer, if the escrow is still open.
+	pub expiry: BlockNumber,
+}
+
+/// The events deposited by the Escrow Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId, Balance> {
+	/// `payer` reserved `amount` for `payee` in the escrow `id`.
+	Created { id: EscrowId, payer: AccountId, payee: AccountId, amount: Balance },
+	/// `amount` was paid to `payee` from the escrow `id`.
+	Released { id: EscrowId, payee: AccountId, amount: Balance },
+	/// The payee refunded `amount` to `payer` from the escrow `id`.
+	Refunded { id: EscrowId, payer: AccountId, amount: Balance },
+	/// The escrow `id` expired, and `amount` returned to `payer`.
+	Expired { id: EscrowId, payer: AccountId, amount: Balance },
+}
+
+/// This is the Escrow Module.
+/// It holds conditional payments: a payer reserves some balance for a payee, which is paid when
+/// the payer or an arbiter releases it. The payee can instead refund the payer, and the balance
+/// returns to the payer automatically when the escrow expires.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The open escrows, by identifier.
+	#[allow(clippy::type_complexity)]
+	escrows: BTreeMap<EscrowId, Escrow<T::AccountId, T::Balance, T::BlockNumber>>,
+	/// The identifier of the next escrow.
+	next_id: EscrowId,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+{
+	/// Reserve `amount` from the caller for `payee`, until `arbiter` or the caller releases it, or
+	/// until the block `expiry`, at which it returns to the caller.
+	pub fn create(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		payee: T::AccountId,
+		arbiter: T::AccountId,
+		amount: T::Balance,
+		expiry: T::BlockNumber,
+	) -> DispatchResult {
+		let payer = ensure_signed(origin)?;
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		if expiry <= system.block_number() {
+			return Err("Expiry must be in the future")
+		}
+
+		let escrow: &Self = runtime.pallet();
+		let id = escrow.next_id;
+		let next_id = id.checked_add(1).ok_or("Escrow id overflow")?;
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.reserve(&payer, amount)?;
+
+		let escrow: &mut Self = runtime.pallet_mut();
+		escrow.next_id = next_id;
+		let entry = Escrow { payer: payer.clone(), payee: payee.clone(), arbiter, amount, expiry };
+		escrow.escrows.insert(id, entry);
+
+		Self::deposit_event(runtime, Event::Created { id, payer, payee, amount });
+		Ok(())
+	}
+
+	/// Pay the balance of the escrow `id` to its payee.
+	/// This function can only be called by the payer or the arbiter of the escrow.
+	pub fn release(runtime: &mut T, origin: T::RuntimeOrigin, id: EscrowId) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let escrow: &Self = runtime.pallet();
+		let entry = escrow.escrows.get(&id).ok_or("Escrow not found")?;
+		if caller != entry.payer && caller != entry.arbiter {
+			return Err("Only the payer or the arbiter can release the escrow")
+		}
+		let Escrow { payer, payee, amount, .. } = entry.clone();
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.repatriate_reserved(&payer, &payee, amount)?;
+
+		let escrow: &mut Self = runtime.pallet_mut();
+		escrow.escrows.remove(&id);
+
+		Self::deposit_event(runtime, Event::Released { id, payee, amount });
+		Ok(())
+	}
+
+	/// Return the balance of the escrow `id` to its payer.
+	/// This function can only be called by the payee of the escrow.
+	pub fn refund(runtime: &mut T, origin: T::RuntimeOrigin, id: EscrowId) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let escrow: &Self = runtime.pallet();
+		let entry = escrow.escrows.get(&id).ok_or("Escrow not found")?;
+		if caller != entry.payee {
+			return Err("Only the payee can refund the escrow")
+		}
+		let Escrow { payer, amount, .. } = entry.clone();
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.unreserve(&payer, amount)?;
+
+		let escrow: &mut Self = runtime.pallet_mut();
+		escrow.escrows.remove(&id);
+
+		Self::deposit_event(runtime, Event::Refunded { id, payer, amount });
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Escrow Pallet.
+	pub fn new() -> Self {
+		Self { escrows: BTreeMap::new(), next_id: 0 }
+	}
+
+	/// Get the escrow `id`, if it is still open.
+	pub fn escrow(
+		&self,
+		id: EscrowId,
+	) -> Option<&Escrow<T::AccountId, T::Balance, T::BlockNumber>> {
+		self.escrows.get(&id)
+	}
+
+	fn deposit_event(runtime: &mut T, event: Event<T::AccountId, T::Balance>)
+	where
+		T: GetPallet<system::Pallet<T>>,
+	{
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(event);
+	}
+}
+
+impl<T> Hooks<T, T::BlockNumber> for Pallet<T>
+where
+	T: Config
+		+ GetPallet<Pallet<T>>
+		+ GetPallet<system::Pallet<T>>
+		+ GetPallet<balances::Pallet<T>>,
+{
+	/// Return the balance of the escrows expiring at the block `n` to their payers.
+	fn on_initialize(runtime: &mut T, n: T::BlockNumber) {
+		let escrow: &mut Self = runtime.pallet_mut();
+		let (expired, open) = std::mem::take(&mut escrow.escrows)
+			.into_iter()
+			.partition(|(_, entry)| entry.expiry <= n);
+		escrow.escrows = open;
+
+		for (id, Escrow { payer, amount, .. }) in expired {
+			let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+			// The amount was reserved when the escrow was created, and is unreserved only once.
+			let _ = balances.unreserve(&payer, amount);
+			Self::deposit_event(runtime, Event::Expired { id, payer, amount });
+		}
+	}
+}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{support::Origin, Runtime, RuntimeEvent};
+
+	#[test]
+	fn create_escrow() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+
+		// Act
+		let res = create(&mut runtime, &alice, &bob, &charlie, 40, 10);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.account(&alice).reserved, 40);
+		assert_eq!(
+			runtime.escrow.escrow(0),
+			Some(&Escrow {
+				payer: alice.clone(),
+				payee: bob.clone(),
+				arbiter: charlie,
+				amount: 40,
+				expiry: 10
+			})
+		);
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::escrow(Event::Created { id: 0, payer: alice, payee: bob, amount: 40 })]
+		);
+	}
+
+	#[test]
+	fn create_escrow_errors() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+
+		// Act & Assert
+		assert_eq!(
+			create(&mut runtime, &alice, &bob, &charlie, 40, 1),
+			Err("Expiry must be in the future")
+		);
+		assert_eq!(create(&mut runtime, &alice, &bob, &charlie, 200, 10), Err("Not enough funds."));
+		assert_eq!(runtime.escrow.escrow(0), None);
+		runtime.escrow.next_id = EscrowId::MAX;
+		assert_eq!(create(&mut runtime, &alice, &bob, &charlie, 40, 10), Err("Escrow id overflow"));
+		assert_eq!(runtime.balances.account(&alice).reserved, 0);
+	}
+
+	#[test]
+	fn release_escrow() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let _ = create(&mut runtime, &alice, &bob, &charlie, 40, 10);
+		let _ = create(&mut runtime, &alice, &bob, &charlie, 20, 10);
+
+		// Act
+		let res = Pallet::release(&mut runtime, Origin::Signed(bob.clone()), 0);
+
+		// Assert
+		assert_eq!(res, Err("Only the payer or the arbiter can release the escrow"));
+		assert_eq!(Pallet::release(&mut runtime, Origin::Signed(charlie), 0), Ok(()));
+		assert_eq!(Pallet::release(&mut runtime, Origin::Signed(alice.clone()), 1), Ok(()));
+		assert_eq!(runtime.balances.account(&alice).reserved, 0);
+		assert_eq!(runtime.balances.balance(&alice), 40);
+		assert_eq!(runtime.balances.balance(&bob), 60);
+		assert_eq!(runtime.escrow.escrow(0), None);
+		assert_eq!(
+			runtime.system.events().last(),
+			Some(&RuntimeEvent::escrow(Event::Released { id: 1, payee: bob, amount: 20 }))
+		);
+		assert_eq!(
+			Pallet::release(&mut runtime, Origin::Signed(alice), 0),
+			Err("Escrow not found")
+		);
+	}
+
+	#[test]
+	fn refund_escrow() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let _ = create(&mut runtime, &alice, &bob, &charlie, 40, 10);
+
+		// Act
+		let res = Pallet::refund(&mut runtime, Origin::Signed(charlie), 0);
+
+		// Assert
+		assert_eq!(res, Err("Only the payee can refund the escrow"));
+		assert_eq!(Pallet::refund(&mut runtime, Origin::Signed(bob.clone()), 0), Ok(()));
+		assert_eq!(runtime.balances.account(&alice).reserved, 0);
+		assert_eq!(runtime.balances.balance(&alice), 100);
+		assert_eq!(runtime.balances.balance(&bob), 0);
+		assert_eq!(runtime.escrow.escrow(0), None);
+		assert_eq!(
+			runtime.system.events().last(),
+			Some(&RuntimeEvent::escrow(Event::Refunded { id: 0, payer: alice, amount: 40 }))
+		);
+	}
+
+	#[test]
+	fn escrow_expires() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let _ = create(&mut runtime, &alice, &bob, &charlie, 40, 10);
+		let _ = create(&mut runtime, &alice, &bob, &charlie, 20, 20);
+
+		// Act
+		Pallet::on_initialize(&mut runtime, 9);
+		let reserved_at_block_9 = runtime.balances.account(&alice).reserved;
+		Pallet::on_initialize(&mut runtime, 10);
+
+		// Assert
+		assert_eq!(reserved_at_block_9, 60);
+		assert_eq!(runtime.balances.account(&alice).reserved, 20);
+		assert_eq!(runtime.balances.balance(&alice), 80);
+		assert_eq!(runtime.escrow.escrow(0), None);
+		assert!(runtime.escrow.escrow(1).is_some());
+		assert_eq!(
+			runtime.system.events().last(),
+			Some(&RuntimeEvent::escrow(Event::Expired { id: 0, payer: alice, amount: 40 }))
+		);
+	}
+
+	fn create(
+		runtime: &mut Runtime,
+		payer: &str,
+		payee: &str,
+		arbiter: &str,
+		amount: u128,
+		expiry: u32,
+	) -> DispatchResult {
+		let origin = Origin::Signed(String::from(payer));
+		Pallet::create(runtime, origin, String::from(payee), String::from(arbiter), amount, expiry)
+	}
+
+	fn setup() -> (Runtime, String, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let charlie = String::from("charlie");
+		let mut runtime = Runtime::new();
+		runtime.system.inc_block_number();
+		runtime.balances.set_balance(&alice, 100);
+
+		(runtime, alice, bob, charlie)
+	}
+}
@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+
+use num::CheckedAdd;
+
+use crate::{
+	balances,
+	support::{
+		DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, Origin, ProvideInherent,
+		TransactionLongevity, TransactionValidity, ValidTransaction, ValidateUnsigned,
+	},
+	system,
+};
+
+pub trait Config:
+	balances::Config
+	+ system::Config<
+		BlockNumber: CheckedAdd + Ord,
+		RuntimeEvent: From<
+			Event<<Self as system::Config>::AccountId, <Self as balances::Config>::Balance>,
+		>,
+	>
+{
+	/// The balance minted to an account by each drip.
+	const DRIP_AMOUNT: Self::Balance;
+	/// The minimum number of blocks between two drips to the same account.
+	const DRIP_INTERVAL: Self::BlockNumber;
+}
+
+/// The events deposited by the Faucet Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId, Balance> {
+	/// `amount` was minted to `who`.
+	Dripped { who: AccountId, amount: Balance },
+}
+
+/// This is the Faucet Module.
+/// It funds the accounts of test networks: anyone can request `DRIP_AMOUNT` of new balance for an
+/// account, at most once every `DRIP_INTERVAL` blocks. As new accounts have no funds, the request
+/// can also be submitted as an unsigned transaction.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The block of the last drip to each account.
+	last_drip: BTreeMap<T::AccountId, T::BlockNumber>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+{
+	/// Mint `DRIP_AMOUNT` to the account `who`.
+	/// This function can be called by any account, or without a caller, but fails if `who`
+	/// already received a drip in the last `DRIP_INTERVAL` blocks.
+	pub fn drip(runtime: &mut T, origin: T::RuntimeOrigin, who: T::AccountId) -> DispatchResult {
+		if let Origin::Root = origin.into() {
+			return Err("Origin must be signed or none")
+		}
+		Self::ensure_can_drip(runtime, &who)?;
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.mint(&who, T::DRIP_AMOUNT)?;
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		let now = system.block_number();
+		let faucet: &mut Self = runtime.pallet_mut();
+		faucet.last_drip.insert(who.clone(), now);
+
+		Self::deposit_event(runtime, Event::Dripped { who, amount: T::DRIP_AMOUNT });
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Faucet Pallet.
+	pub fn new() -> Self {
+		Self { last_drip: BTreeMap::new() }
+	}
+
+	/// Get the block of the last drip to the account `who`, if any.
+	pub fn last_drip(&self, who: &T::AccountId) -> Option<T::BlockNumber> {
+		self.last_drip.get(who).copied()
+	}
+}
+
+impl<T> Pallet<T>
+where
+	T: Config + GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>>,
+{
+	/// Check that the account `who` did not receive a drip in the last `DRIP_INTERVAL` blocks.
+	fn ensure_can_drip(runtime: &T, who: &T::AccountId) -> DispatchResult {
+		let faucet: &Self = runtime.pallet();
+		if let Some(last_drip) = faucet.last_drip(who) {
+			let next_drip =
+				last_drip.checked_add(&T::DRIP_INTERVAL).ok_or("Block number overflow")?;
+			let system: &system::Pallet<T> = runtime.pallet();
+			if system.block_number() < next_drip {
+				return Err("Drip requested too early")
+			}
+		}
+		Ok(())
+	}
+
+	fn deposit_event(runtime: &mut T, event: Event<T::AccountId, T::Balance>) {
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(event);
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+/// The `drip` call is accepted as an unsigned transaction, as long as the account can receive a
+/// drip. It stays valid until the drip is made.
+impl<T> ValidateUnsigned<T, Call<T>> for Pallet<T>
+where
+	T: Config + GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>>,
+{
+	fn validate_unsigned(runtime: &T, call: &Call<T>) -> TransactionValidity {
+		match call {
+			Call::drip { who } => {
+				Self::ensure_can_drip(runtime, who)?;
+				Ok(ValidTransaction { priority: 0, longevity: TransactionLongevity::MAX })
+			},
+			_ => Err("Unsigned transaction is not allowed"),
+		}
+	}
+}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		support::{Extrinsic, Header},
+		timestamp, types, Runtime, RuntimeCall, RuntimeEvent,
+	};
+
+	#[test]
+	fn drip_mints_to_account() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+
+		// Act
+		let res = Pallet::drip(&mut runtime, Origin::Signed(alice), bob.clone());
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&bob), 100);
+		assert_eq!(runtime.balances.total_issuance(), 100);
+		assert_eq!(runtime.faucet.last_drip(&bob), Some(1));
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::faucet(Event::Dripped { who: bob, amount: 100 })]
+		);
+	}
+
+	#[test]
+	fn drip_is_rate_limited() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::drip(&mut runtime, Origin::None, bob.clone());
+		for _ in 1..10 {
+			runtime.system.inc_block_number();
+		}
+
+		// Act
+		let res = Pallet::drip(&mut runtime, Origin::None, bob.clone());
+
+		// Assert
+		assert_eq!(res, Err("Drip requested too early"));
+		assert_eq!(Pallet::drip(&mut runtime, Origin::None, alice), Ok(()));
+		runtime.system.inc_block_number();
+		assert_eq!(Pallet::drip(&mut runtime, Origin::None, bob.clone()), Ok(()));
+		assert_eq!(runtime.balances.balance(&bob), 200);
+		assert_eq!(runtime.balances.total_issuance(), 300);
+	}
+
+	#[test]
+	fn drip_rejects_root_origin() {
+		// Arrange
+		let (mut runtime, _, bob) = setup();
+
+		// Act
+		let res = Pallet::drip(&mut runtime, Origin::Root, bob.clone());
+
+		// Assert
+		assert_eq!(res, Err("Origin must be signed or none"));
+		assert_eq!(runtime.balances.balance(&bob), 0);
+	}
+
+	#[test]
+	fn validate_unsigned_drip() {
+		// Arrange
+		let (mut runtime, _, bob) = setup();
+		let call = RuntimeCall::faucet(Call::drip { who: bob.clone() });
+
+		// Act
+		let validity = runtime.validate_unsigned(&call);
+		let _ = Pallet::drip(&mut runtime, Origin::None, bob);
+
+		// Assert
+		assert_eq!(
+			validity,
+			Ok(ValidTransaction { priority: 0, longevity: TransactionLongevity::MAX })
+		);
+		assert_eq!(runtime.validate_unsigned(&call), Err("Drip requested too early"));
+	}
+
+	#[test]
+	fn execute_block_with_unsigned_drip() {
+		// Arrange
+		let mut runtime = Runtime::new();
+		let bob = String::from("bob");
+		let drip = || Extrinsic {
+			caller: None,
+			call: RuntimeCall::faucet(Call::drip { who: bob.clone() }),
+		};
+
+		// Act
+		let res = runtime.execute_block(block(1, vec![drip()]));
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&bob), 100);
+		assert_eq!(runtime.execute_block(block(2, vec![drip()])), Err("Drip requested too early"));
+	}
+
+	fn block(block_number: types::BlockNumber, extrinsics: Vec<types::Extrinsic>) -> types::Block {
+		let inherent = Extrinsic {
+			caller: None,
+			call: RuntimeCall::timestamp(timestamp::Call::set {
+				now: types::Moment::from(block_number) * 1_000,
+			}),
+		};
+		types::Block {
+			header: Header { parent_hash: 0, block_number },
+			extrinsics: [vec![inherent], extrinsics].concat(),
+		}
+	}
+
+	fn setup() -> (Runtime, types::AccountId, types::AccountId) {
+		let mut runtime = Runtime::new();
+		runtime.system.inc_block_number();
+
+		(runtime, String::from("alice"), String::from("bob"))
+	}
+}
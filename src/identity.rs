@@ -0,0 +1,403 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{
+	balances,
+	support::{
+		DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, ProvideInherent, ValidateUnsigned,
+	},
+	system::{self, ensure_root, ensure_signed},
+};
+
+/// The index of a registrar.
+pub type RegistrarIndex = u32;
+
+pub trait Config:
+	balances::Config
+	+ system::Config<RuntimeEvent: From<Event<<Self as system::Config>::AccountId>>>
+	+ Sized
+{
+	/// The balance reserved from an account while it has an identity.
+	const BASIC_DEPOSIT: Self::Balance;
+}
+
+/// The human-readable information of an identity.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IdentityInfo {
+	/// The name to display for the account.
+	pub display: String,
+	/// The email address of the owner of the account.
+	pub email: String,
+	/// The website of the owner of the account.
+	pub web: String,
+}
+
+/// The attestation of a registrar on the information of an identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Judgement {
+	/// The information is believed to be correct.
+	Reasonable,
+	/// The information was checked, and is correct.
+	KnownGood,
+	/// The information is wrong.
+	Erroneous,
+}
+
+/// The identity of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registration<Balance> {
+	/// The information set by the account.
+	pub info: IdentityInfo,
+	/// The balance reserved from the account.
+	pub deposit: Balance,
+	/// The judgements of the registrars on the information.
+	pub judgements: BTreeMap<RegistrarIndex, Judgement>,
+}
+
+/// The events deposited by the Identity Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId> {
+	/// `who` set its identity.
+	IdentitySet { who: AccountId },
+	/// `who` cleared its identity, and got its deposit back.
+	IdentityCleared { who: AccountId },
+	/// `registrar` was added as the registrar `registrar_index`.
+	RegistrarAdded { registrar_index: RegistrarIndex, registrar: AccountId },
+	/// The registrar `registrar_index` gave a judgement on the identity of `target`.
+	JudgementGiven { target: AccountId, registrar_index: RegistrarIndex },
+}
+
+/// This is the Identity Module.
+/// It allows accounts to set a display name, an email address and a website, by reserving a
+/// deposit, and registrars appointed by the root origin to attest to this information.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The identity of each account.
+	identities: BTreeMap<T::AccountId, Registration<T::Balance>>,
+	/// The registrars, by index.
+	registrars: Vec<T::AccountId>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+{
+	/// Set the identity of the caller to `info`, reserving `BASIC_DEPOSIT` if it has no identity
+	/// yet. The judgements on a previous identity are removed.
+	pub fn set_identity(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		info: IdentityInfo,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		if info.display.is_empty() {
+			return Err("Display name must not be empty")
+		}
+
+		let identity: &Self = runtime.pallet();
+		let deposit = match identity.identities.get(&caller) {
+			Some(registration) => registration.deposit,
+			None => {
+				let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+				balances.reserve(&caller, T::BASIC_DEPOSIT)?;
+				T::BASIC_DEPOSIT
+			},
+		};
+
+		let identity: &mut Self = runtime.pallet_mut();
+		let registration = Registration { info, deposit, judgements: BTreeMap::new() };
+		identity.identities.insert(caller.clone(), registration);
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::IdentitySet { who: caller });
+		Ok(())
+	}
+
+	/// Clear the identity of the caller, and unreserve its deposit.
+	pub fn clear_identity(runtime: &mut T, origin: T::RuntimeOrigin) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let identity: &mut Self = runtime.pallet_mut();
+		let registration = identity.identities.remove(&caller).ok_or("No identity found")?;
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.unreserve(&caller, registration.deposit)?;
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::IdentityCleared { who: caller });
+		Ok(())
+	}
+
+	/// Add `registrar` as a new registrar.
+	/// This function can only be called by the root origin.
+	pub fn add_registrar(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		registrar: T::AccountId,
+	) -> DispatchResult {
+		ensure_root(origin)?;
+
+		let identity: &mut Self = runtime.pallet_mut();
+		if identity.registrars.contains(&registrar) {
+			return Err("Account is already a registrar")
+		}
+		let registrar_index =
+			identity.registrars.len().try_into().map_err(|_| "Registrar index overflow")?;
+		identity.registrars.push(registrar.clone());
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::RegistrarAdded { registrar_index, registrar });
+		Ok(())
+	}
+
+	/// Give the `judgement` of the registrar `registrar_index` on the identity of `target`.
+	/// This function fails if the caller is not that registrar.
+	pub fn provide_judgement(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		registrar_index: RegistrarIndex,
+		target: T::AccountId,
+		judgement: Judgement,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let identity: &mut Self = runtime.pallet_mut();
+		let registrar = identity.registrar(registrar_index).ok_or("Registrar not found")?;
+		if *registrar != caller {
+			return Err("Caller is not the registrar")
+		}
+		let registration = identity.identities.get_mut(&target).ok_or("No identity found")?;
+		registration.judgements.insert(registrar_index, judgement);
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::JudgementGiven { target, registrar_index });
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Identity Pallet.
+	pub fn new() -> Self {
+		Self { identities: BTreeMap::new(), registrars: Vec::new() }
+	}
+
+	/// Get the identity of `who`, if any.
+	pub fn identity(&self, who: &T::AccountId) -> Option<&Registration<T::Balance>> {
+		self.identities.get(who)
+	}
+
+	/// Get the display name of `who`, if it has an identity.
+	pub fn display_name(&self, who: &T::AccountId) -> Option<&str> {
+		self.identity(who).map(|registration| registration.info.display.as_str())
+	}
+
+	/// Label the account `who` with its display name, e.g. `alice (Alice)`, so that the accounts
+	/// can be shown next to their names. Accounts without an identity are shown as they are.
+	pub fn label(&self, who: &T::AccountId) -> String
+	where
+		T::AccountId: Display,
+	{
+		match self.display_name(who) {
+			Some(name) => format!("{who} ({name})"),
+			None => who.to_string(),
+		}
+	}
+
+	/// Get the account of the registrar `index`, if any.
+	pub fn registrar(&self, index: RegistrarIndex) -> Option<&T::AccountId> {
+		self.registrars.get(index as usize)
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{support::Origin, Runtime, RuntimeEvent};
+
+	#[test]
+	fn set_identity_reserves_deposit() {
+		// Arrange
+		let (mut runtime, alice, _) = setup();
+
+		// Act
+		let res = Pallet::set_identity(&mut runtime, Origin::Signed(alice.clone()), info("Alice"));
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.identity.display_name(&alice), Some("Alice"));
+		assert_eq!(runtime.balances.account(&alice).free, 90);
+		assert_eq!(runtime.balances.account(&alice).reserved, 10);
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::identity(Event::IdentitySet { who: alice })]
+		);
+	}
+
+	#[test]
+	fn label_shows_display_name() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::set_identity(&mut runtime, Origin::Signed(alice.clone()), info("Alice"));
+
+		// Act & Assert
+		assert_eq!(runtime.identity.label(&alice), "alice (Alice)");
+		assert_eq!(runtime.identity.label(&bob), "bob");
+	}
+
+	#[test]
+	fn set_identity_again_keeps_deposit_and_clears_judgements() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::set_identity(&mut runtime, Origin::Signed(alice.clone()), info("Alice"));
+		let _ = Pallet::add_registrar(&mut runtime, Origin::Root, bob.clone());
+		let _ = Pallet::provide_judgement(
+			&mut runtime,
+			Origin::Signed(bob),
+			0,
+			alice.clone(),
+			Judgement::KnownGood,
+		);
+
+		// Act
+		let res = Pallet::set_identity(&mut runtime, Origin::Signed(alice.clone()), info("Al"));
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		let registration = runtime.identity.identity(&alice).expect("alice has an identity");
+		assert_eq!(registration.info.display, "Al");
+		assert!(registration.judgements.is_empty());
+		assert_eq!(runtime.balances.account(&alice).reserved, 10);
+	}
+
+	#[test]
+	fn clear_identity_unreserves_deposit() {
+		// Arrange
+		let (mut runtime, alice, _) = setup();
+		let _ = Pallet::set_identity(&mut runtime, Origin::Signed(alice.clone()), info("Alice"));
+
+		// Act
+		let res = Pallet::clear_identity(&mut runtime, Origin::Signed(alice.clone()));
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.identity.identity(&alice), None);
+		assert_eq!(runtime.balances.account(&alice).free, 100);
+		assert_eq!(runtime.balances.account(&alice).reserved, 0);
+		assert_eq!(
+			Pallet::clear_identity(&mut runtime, Origin::Signed(alice)),
+			Err("No identity found")
+		);
+	}
+
+	#[test]
+	fn provide_judgement_success() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::set_identity(&mut runtime, Origin::Signed(alice.clone()), info("Alice"));
+		let _ = Pallet::add_registrar(&mut runtime, Origin::Root, bob.clone());
+
+		// Act
+		let res = Pallet::provide_judgement(
+			&mut runtime,
+			Origin::Signed(bob.clone()),
+			0,
+			alice.clone(),
+			Judgement::Reasonable,
+		);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.identity.registrar(0), Some(&bob));
+		let registration = runtime.identity.identity(&alice).expect("alice has an identity");
+		assert_eq!(registration.judgements.get(&0), Some(&Judgement::Reasonable));
+		assert_eq!(
+			runtime.system.events().last(),
+			Some(&RuntimeEvent::identity(Event::JudgementGiven {
+				target: alice,
+				registrar_index: 0
+			}))
+		);
+	}
+
+	#[test]
+	fn identity_errors() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::add_registrar(&mut runtime, Origin::Root, bob.clone());
+
+		// Act & Assert
+		assert_eq!(
+			Pallet::set_identity(&mut runtime, Origin::Signed(alice.clone()), info("")),
+			Err("Display name must not be empty")
+		);
+		assert_eq!(
+			Pallet::set_identity(&mut runtime, Origin::Signed(bob.clone()), info("Bob")),
+			Err("Not enough funds.")
+		);
+		assert_eq!(
+			Pallet::add_registrar(&mut runtime, Origin::Signed(alice.clone()), alice.clone()),
+			Err("Origin must be root")
+		);
+		assert_eq!(
+			Pallet::add_registrar(&mut runtime, Origin::Root, bob.clone()),
+			Err("Account is already a registrar")
+		);
+		assert_eq!(
+			Pallet::provide_judgement(
+				&mut runtime,
+				Origin::Signed(bob.clone()),
+				1,
+				alice.clone(),
+				Judgement::Erroneous
+			),
+			Err("Registrar not found")
+		);
+		assert_eq!(
+			Pallet::provide_judgement(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				0,
+				alice.clone(),
+				Judgement::Erroneous
+			),
+			Err("Caller is not the registrar")
+		);
+		assert_eq!(
+			Pallet::provide_judgement(
+				&mut runtime,
+				Origin::Signed(bob),
+				0,
+				alice,
+				Judgement::Erroneous
+			),
+			Err("No identity found")
+		);
+	}
+
+	fn info(display: &str) -> IdentityInfo {
+		IdentityInfo { display: display.to_string(), ..Default::default() }
+	}
+
+	fn setup() -> (Runtime, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&alice, 100);
+
+		(runtime, alice, bob)
+	}
+}
@@ -1,15 +1,37 @@
 use support::{Dispatch, DispatchResult};
 
+pub mod assets;
 pub mod balances;
+pub mod block_tree;
+pub mod democracy;
+pub mod escrow;
+pub mod faucet;
+pub mod identity;
+pub mod multisig;
+pub mod nfts;
 pub mod proof_of_existence;
+pub mod proxy;
+pub mod randomness;
+pub mod scheduler;
+pub mod staking;
+pub mod sudo;
 pub mod support;
 pub mod system;
+pub mod timestamp;
+pub mod treasury;
+pub mod utility;
+pub mod vesting;
 
 pub mod types {
 	use crate::{support, RuntimeCall};
 
 	pub type AccountId = String;
+	pub type RuntimeOrigin = support::Origin<AccountId>;
 	pub type Balance = u128;
+	pub type AssetId = u32;
+	pub type CollectionId = u32;
+	pub type ItemId = u32;
+	pub type Moment = u64;
 	pub type BlockNumber = u32;
 	pub type Nonce = u32;
 	pub type Extrinsic = support::Extrinsic<AccountId, RuntimeCall>;
@@ -18,12 +40,40 @@ pub mod types {
 	pub type Content = &'static str;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[macros::runtime]
 pub struct Runtime {
 	pub system: system::Pallet<Runtime>,
 	pub balances: balances::Pallet<Runtime>,
 	pub proof_of_existence: proof_of_existence::Pallet<Runtime>,
+	#[event(sudo::Event<types::AccountId>)]
+	pub sudo: sudo::Pallet<Runtime>,
+	#[event(utility::Event)]
+	pub utility: utility::Pallet<Runtime>,
+	#[event(multisig::Event<types::AccountId>)]
+	pub multisig: multisig::Pallet<Runtime>,
+	#[event(proxy::Event<types::AccountId, types::BlockNumber>)]
+	pub proxy: proxy::Pallet<Runtime>,
+	#[event(scheduler::Event<types::BlockNumber>)]
+	pub scheduler: scheduler::Pallet<Runtime>,
+	#[event(vesting::Event<types::AccountId, types::Balance>)]
+	pub vesting: vesting::Pallet<Runtime>,
+	pub assets: assets::Pallet<Runtime>,
+	pub nfts: nfts::Pallet<Runtime>,
+	#[event(staking::Event<types::AccountId, types::Balance>)]
+	pub staking: staking::Pallet<Runtime>,
+	#[event(democracy::Event<types::AccountId, types::BlockNumber>)]
+	pub democracy: democracy::Pallet<Runtime>,
+	#[event(treasury::Event<types::AccountId, types::Balance>)]
+	pub treasury: treasury::Pallet<Runtime>,
+	#[event(identity::Event<types::AccountId>)]
+	pub identity: identity::Pallet<Runtime>,
+	pub randomness: randomness::Pallet<Runtime>,
+	pub timestamp: timestamp::Pallet<Runtime>,
+	#[event(faucet::Event<types::AccountId, types::Balance>)]
+	pub faucet: faucet::Pallet<Runtime>,
+	#[event(escrow::Event<types::AccountId, types::Balance>)]
+	pub escrow: escrow::Pallet<Runtime>,
 }
 
 impl Runtime {
@@ -38,6 +88,8 @@ impl Runtime {
 
 impl system::Config for Runtime {
 	type AccountId = types::AccountId;
+	type RuntimeOrigin = types::RuntimeOrigin;
+	type RuntimeEvent = RuntimeEvent;
 	type BlockNumber = types::BlockNumber;
 	type Nonce = types::Nonce;
 }
@@ -50,6 +102,77 @@ impl proof_of_existence::Config for Runtime {
 	type Content = types::Content;
 }
 
+impl sudo::Config for Runtime {
+	type RuntimeCall = RuntimeCall;
+}
+
+impl utility::Config for Runtime {
+	type RuntimeCall = RuntimeCall;
+}
+
+impl multisig::Config for Runtime {
+	const DEPOSIT: Self::Balance = 10;
+	type RuntimeCall = RuntimeCall;
+}
+
+impl proxy::Config for Runtime {
+	type RuntimeCall = RuntimeCall;
+}
+
+impl scheduler::Config for Runtime {
+	const MAX_SCHEDULED_PER_BLOCK: u32 = 5;
+	type RuntimeCall = RuntimeCall;
+}
+
+impl vesting::Config for Runtime {}
+
+impl assets::Config for Runtime {
+	type AssetId = types::AssetId;
+	type Balance = types::Balance;
+}
+
+impl nfts::Config for Runtime {
+	type CollectionId = types::CollectionId;
+	type ItemId = types::ItemId;
+}
+
+impl staking::Config for Runtime {
+	const ERA_LENGTH: Self::BlockNumber = 10;
+	const BONDING_DURATION: Self::BlockNumber = 20;
+	const ERA_REWARD: Self::Balance = 100;
+	const MAX_VALIDATORS: u32 = 3;
+	type Randomness = randomness::Pallet<Runtime>;
+}
+
+impl democracy::Config for Runtime {
+	const VOTING_PERIOD: Self::BlockNumber = 10;
+	const ENACTMENT_DELAY: Self::BlockNumber = 5;
+	type RuntimeCall = RuntimeCall;
+}
+
+impl treasury::Config for Runtime {
+	const SPEND_PERIOD: Self::BlockNumber = 10;
+	const PROPOSAL_BOND: Self::Balance = 5;
+}
+
+impl identity::Config for Runtime {
+	const BASIC_DEPOSIT: Self::Balance = 10;
+}
+
+impl randomness::Config for Runtime {}
+
+impl timestamp::Config for Runtime {
+	type Moment = types::Moment;
+	const MINIMUM_PERIOD: Self::Moment = 1_000;
+}
+
+impl faucet::Config for Runtime {
+	const DRIP_AMOUNT: Self::Balance = 100;
+	const DRIP_INTERVAL: Self::BlockNumber = 10;
+}
+
+impl escrow::Config for Runtime {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,4 +185,143 @@ mod tests {
         // Assert
         assert!(runtime.system.block_number() == 0);
     }
+
+    #[test]
+    fn test_validate_unsigned_rejects_by_default() {
+        // Arrange
+        let runtime = Runtime::instace();
+        let call = RuntimeCall::balances(balances::Call::transfer {
+            to: String::from("bob"),
+            amount: 10,
+        });
+
+        // Act
+        let validity = runtime.validate_unsigned(&call);
+
+        // Assert
+        assert_eq!(validity, Err("Unsigned transaction is not allowed"));
+    }
+
+    #[test]
+    fn test_rejected_block_leaves_runtime_unchanged() {
+        // Arrange
+        let mut runtime = Runtime::instace();
+        runtime.balances.set_balance(&String::from("alice"), 100);
+        let before = format!("{:?}", runtime);
+        let block = types::Block {
+            header: support::Header { parent_hash: 42, block_number: 1 },
+            extrinsics: vec![support::Extrinsic {
+                caller: None,
+                call: RuntimeCall::timestamp(timestamp::Call::set { now: 0 }),
+            }],
+        };
+
+        // Act
+        let result = runtime.execute_block(block);
+
+        // Assert
+        assert_eq!(result, Err("Timestamp must increment by at least the minimum period"));
+        assert_eq!(format!("{:?}", runtime), before);
+        assert_eq!(runtime.system.block_number(), 0);
+    }
+
+    #[test]
+    fn test_invalid_unsigned_transaction_leaves_runtime_unchanged() {
+        // Arrange
+        let mut runtime = Runtime::instace();
+        let alice = String::from("alice");
+        runtime.balances.set_balance(&alice, 100);
+        let before = format!("{:?}", runtime);
+        let transfer = RuntimeCall::balances(balances::Call::transfer {
+            to: String::from("bob"),
+            amount: 10,
+        });
+        let block = types::Block {
+            header: support::Header { parent_hash: 42, block_number: 1 },
+            extrinsics: vec![
+                support::Extrinsic {
+                    caller: None,
+                    call: RuntimeCall::timestamp(timestamp::Call::set { now: 1_000 }),
+                },
+                support::Extrinsic { caller: Some(alice.clone()), call: transfer.clone() },
+                support::Extrinsic { caller: None, call: transfer },
+            ],
+        };
+
+        // Act
+        let result = runtime.execute_block(block);
+
+        // Assert
+        assert_eq!(result, Err("Unsigned transaction is not allowed"));
+        assert_eq!(format!("{:?}", runtime), before);
+        assert_eq!(runtime.system.get_nonce(&alice), 0);
+        assert_eq!(runtime.balances.balance(&alice), 100);
+    }
+
+    #[test]
+    fn test_hooks_run_around_extrinsics_in_declaration_order() {
+        // Arrange
+        let mut runtime = Runtime::instace();
+        let alice = String::from("alice");
+        let bob = String::from("bob");
+        runtime.balances.set_balance(&alice, 100);
+        runtime.balances.set_balance(&bob, 50);
+        runtime.balances.set_balance(&treasury::Pallet::<Runtime>::account_id(), 100);
+        let set_balance = |amount| {
+            Box::new(RuntimeCall::balances(balances::Call::force_set_balance {
+                who: String::from("charlie"),
+                amount,
+            }))
+        };
+        let root = support::Origin::Root;
+        let signed = support::Origin::Signed(alice.clone());
+        // Dispatched by `scheduler` and ended by `democracy` when block 10 is initialized.
+        let _ = scheduler::Pallet::schedule(&mut runtime, root.clone(), 10, None, set_balance(1));
+        let _ = democracy::Pallet::propose(&mut runtime, signed.clone(), set_balance(2));
+        // Paid by `staking` and `treasury` when block 10 is finalized.
+        let _ = treasury::Pallet::propose_spend(&mut runtime, signed.clone(), 10, bob.clone());
+        let _ = treasury::Pallet::approve_proposal(&mut runtime, root, 0);
+        let _ = democracy::Pallet::vote(&mut runtime, signed, 0, true);
+        for _ in 0..9 {
+            runtime.system.inc_block_number();
+        }
+        let block = types::Block {
+            header: support::Header { parent_hash: 0, block_number: 10 },
+            extrinsics: vec![
+                support::Extrinsic {
+                    caller: None,
+                    call: RuntimeCall::timestamp(timestamp::Call::set { now: 10_000 }),
+                },
+                support::Extrinsic {
+                    caller: Some(bob.clone()),
+                    call: RuntimeCall::identity(identity::Call::set_identity {
+                        info: identity::IdentityInfo {
+                            display: String::from("Bob"),
+                            ..Default::default()
+                        },
+                    }),
+                },
+            ],
+        };
+
+        // Act
+        let result = runtime.execute_block(block);
+
+        // Assert
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            runtime.system.events(),
+            &[
+                RuntimeEvent::scheduler(scheduler::Event::Dispatched { id: 0, result: Ok(()) }),
+                RuntimeEvent::democracy(democracy::Event::Passed { index: 0, enactment: 15 }),
+                RuntimeEvent::identity(identity::Event::IdentitySet { who: bob.clone() }),
+                RuntimeEvent::staking(staking::Event::EraPaid { era_index: 0 }),
+                RuntimeEvent::treasury(treasury::Event::Awarded {
+                    index: 0,
+                    award: 10,
+                    beneficiary: bob,
+                }),
+            ]
+        );
+    }
 }
@@ -1,7 +1,9 @@
 use rust_state_machine::{
-	balances, proof_of_existence,
+	balances,
+	block_tree::BlockTree,
+	faucet, identity, proof_of_existence, sudo,
 	support::{self, Header},
-	types, Runtime, RuntimeCall,
+	timestamp, types, Runtime, RuntimeCall,
 };
 
 fn main() {
@@ -12,19 +14,30 @@ fn main() {
 	let charlie = String::from("charlie");
 
 	runtime.balances.set_balance(&alice, 100);
+	runtime.sudo.initialize_key(&alice);
+
+	let mut block_tree = BlockTree::new(runtime);
 
 	let block_1 = types::Block {
-		header: Header { block_number: 1 },
+		header: Header { parent_hash: block_tree.genesis_hash(), block_number: 1 },
 		extrinsics: vec![
 			support::Extrinsic {
-				caller: alice.clone(),
+				caller: None,
+				call: RuntimeCall::timestamp(timestamp::Call::set { now: 1_000 }),
+			},
+			support::Extrinsic {
+				caller: None,
+				call: RuntimeCall::faucet(faucet::Call::drip { who: charlie.clone() }),
+			},
+			support::Extrinsic {
+				caller: Some(alice.clone()),
 				call: RuntimeCall::balances(balances::Call::transfer {
 					to: bob.clone(),
 					amount: 30,
 				}),
 			},
 			support::Extrinsic {
-				caller: alice.clone(),
+				caller: Some(alice.clone()),
 				call: RuntimeCall::balances(balances::Call::transfer {
 					to: charlie.clone(),
 					amount: 20,
@@ -33,27 +46,60 @@ fn main() {
 		],
 	};
 
-	runtime.execute(block_1).expect("Block handling error");
+	block_tree.import(block_1).expect("Block handling error");
 
 	let block_2 = types::Block {
-		header: Header { block_number: 2 },
+		header: Header { parent_hash: block_tree.best_hash(), block_number: 2 },
 		extrinsics: vec![
 			support::Extrinsic {
-				caller: alice.clone(),
+				caller: None,
+				call: RuntimeCall::timestamp(timestamp::Call::set { now: 2_000 }),
+			},
+			support::Extrinsic {
+				caller: Some(alice.clone()),
 				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
 					claim: "Alice's document",
 				}),
 			},
 			support::Extrinsic {
-				caller: bob.clone(),
+				caller: Some(bob.clone()),
 				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
 					claim: "Bob's document",
 				}),
 			},
+			support::Extrinsic {
+				caller: Some(alice.clone()),
+				call: RuntimeCall::sudo(sudo::Call::sudo {
+					call: Box::new(RuntimeCall::balances(balances::Call::force_set_balance {
+						who: charlie.clone(),
+						amount: 50,
+					})),
+				}),
+			},
+			support::Extrinsic {
+				caller: Some(alice.clone()),
+				call: RuntimeCall::identity(identity::Call::set_identity {
+					info: identity::IdentityInfo {
+						display: String::from("Alice"),
+						..Default::default()
+					},
+				}),
+			},
 		],
 	};
 
-	runtime.execute(block_2).expect("Block handling error");
+	block_tree.import(block_2).expect("Block handling error");
+
+	let state = block_tree.best_state();
+	println!("{:#?}", state);
 
-	println!("{:#?}", runtime);
+	// The accounts are only keys in the dump above, so also show them next to their names.
+	for who in [&alice, &bob, &charlie] {
+		println!(
+			"{}: balance {}, nonce {}",
+			state.identity.label(who),
+			state.balances.balance(who),
+			state.system.get_nonce(who)
+		);
+	}
 }
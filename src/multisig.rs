@@ -0,0 +1,531 @@
+use std::{collections::BTreeMap, hash::Hash as StdHash};
+
+use crate::{
+	balances,
+	support::{
+		hash_of, Dispatch, DispatchResult, GetPallet, Hash, Hooks, OnRuntimeUpgrade, Origin,
+		ProvideInherent, ValidateUnsigned,
+	},
+	system::{self, ensure_signed},
+};
+
+pub trait Config:
+	balances::Config
+	+ system::Config<
+		AccountId: From<String> + StdHash,
+		RuntimeEvent: From<Event<<Self as system::Config>::AccountId>>,
+	> + Sized
+{
+	/// The amount reserved from the first signatory of a call, until the call is executed or
+	/// cancelled.
+	const DEPOSIT: Self::Balance;
+	/// The type of the calls which can be dispatched from a multisig account.
+	type RuntimeCall: StdHash;
+}
+
+/// The events deposited by the Multisig Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId> {
+	/// A new multisig operation was started by the first approval of `approving`.
+	NewMultisig { approving: AccountId, multisig: AccountId, call_hash: Hash },
+	/// A pending multisig operation was approved by `approving`.
+	MultisigApproval { approving: AccountId, multisig: AccountId, call_hash: Hash },
+	/// A multisig operation reached its threshold, and its call was dispatched with the given
+	/// result.
+	MultisigExecuted {
+		approving: AccountId,
+		multisig: AccountId,
+		call_hash: Hash,
+		result: DispatchResult,
+	},
+	/// A pending multisig operation was cancelled by its depositor.
+	MultisigCancelled { cancelling: AccountId, multisig: AccountId, call_hash: Hash },
+}
+
+/// A pending multisig operation, waiting for enough approvals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Multisig<AccountId, Balance> {
+	/// The signatory who started the operation, and paid the deposit.
+	pub depositor: AccountId,
+	/// The amount reserved from the depositor.
+	pub deposit: Balance,
+	/// The signatories who approved the operation so far.
+	pub approvals: Vec<AccountId>,
+}
+
+/// This is the Multisig Module.
+/// It allows a group of signatories to control an account together: a call is dispatched from the
+/// multisig account only once a threshold of signatories approved it.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The pending operations, by multisig account and hash of the call.
+	#[allow(clippy::type_complexity)]
+	multisigs: BTreeMap<(T::AccountId, Hash), Multisig<T::AccountId, T::Balance>>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: Dispatch<Origin = T::RuntimeOrigin, Call = <T as Config>::RuntimeCall>
+		+ GetPallet<Pallet<T>>
+		+ GetPallet<system::Pallet<T>>
+		+ GetPallet<balances::Pallet<T>>,
+{
+	/// Approve the call with the hash `call_hash`, as one of `other_signatories` plus the caller,
+	/// with the given `threshold`.
+	/// The first approval of a call reserves a deposit from the caller. The call itself is only
+	/// dispatched by `as_multi`.
+	pub fn approve_as_multi(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		threshold: u32,
+		other_signatories: Vec<T::AccountId>,
+		call_hash: Hash,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let signatories = Self::signatories(&caller, other_signatories, threshold)?;
+		let multisig = Self::multi_account_id(&signatories, threshold);
+
+		Self::approve(runtime, caller, multisig, call_hash)?;
+		Ok(())
+	}
+
+	/// Approve `call`, as one of `other_signatories` plus the caller, with the given `threshold`.
+	/// If the threshold is met with this approval, the call is dispatched from the multisig
+	/// account, and the deposit is returned. The result of the call is recorded in a
+	/// `MultisigExecuted` event.
+	#[allow(clippy::boxed_local)]
+	pub fn as_multi(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		threshold: u32,
+		other_signatories: Vec<T::AccountId>,
+		call: Box<<T as Config>::RuntimeCall>,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let signatories = Self::signatories(&caller, other_signatories, threshold)?;
+		let multisig = Self::multi_account_id(&signatories, threshold);
+		let call_hash = hash_of(&call);
+
+		// A signatory who already approved the call can still execute it, once the other
+		// signatories approved it too.
+		let multisig_pallet: &Self = runtime.pallet();
+		let approvals = match multisig_pallet.multisig(&multisig, call_hash) {
+			Some(pending) if pending.approvals.contains(&caller) => Some(pending.approvals.len()),
+			_ => None,
+		};
+		let approvals = match approvals {
+			Some(approvals) => approvals,
+			None => Self::approve(runtime, caller.clone(), multisig.clone(), call_hash)?,
+		};
+		if approvals < threshold as usize {
+			return Ok(())
+		}
+
+		let multisig_pallet: &mut Self = runtime.pallet_mut();
+		if let Some(pending) = multisig_pallet.multisigs.remove(&(multisig.clone(), call_hash)) {
+			let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+			balances.unreserve(&pending.depositor, pending.deposit)?;
+		}
+
+		let result = runtime.dispatch(Origin::Signed(multisig.clone()).into(), *call);
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::MultisigExecuted {
+			approving: caller,
+			multisig,
+			call_hash,
+			result,
+		});
+		Ok(())
+	}
+
+	/// Cancel the pending call with the hash `call_hash`, returning the deposit.
+	/// This function fails if the caller is not the signatory who started the operation.
+	pub fn cancel_as_multi(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		threshold: u32,
+		other_signatories: Vec<T::AccountId>,
+		call_hash: Hash,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let signatories = Self::signatories(&caller, other_signatories, threshold)?;
+		let multisig = Self::multi_account_id(&signatories, threshold);
+
+		let multisig_pallet: &mut Self = runtime.pallet_mut();
+		let key = (multisig.clone(), call_hash);
+		let pending = multisig_pallet.multisigs.get(&key).ok_or("Multisig operation not found")?;
+		if pending.depositor != caller {
+			return Err("Caller is not the depositor of the multisig operation")
+		}
+		let deposit = pending.deposit;
+		multisig_pallet.multisigs.remove(&key);
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.unreserve(&caller, deposit)?;
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::MultisigCancelled { cancelling: caller, multisig, call_hash });
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Multisig Pallet.
+	pub fn new() -> Self {
+		Self { multisigs: BTreeMap::new() }
+	}
+
+	/// Derive the account controlled by the sorted `signatories` with the given `threshold`.
+	/// The same group of signatories always controls the same account, whoever makes the call.
+	pub fn multi_account_id(signatories: &[T::AccountId], threshold: u32) -> T::AccountId {
+		format!("multisig:{:016x}", hash_of(&(signatories, threshold))).into()
+	}
+
+	/// Get the pending operation of the account `multisig` for the call with the hash `call_hash`.
+	pub fn multisig(
+		&self,
+		multisig: &T::AccountId,
+		call_hash: Hash,
+	) -> Option<&Multisig<T::AccountId, T::Balance>> {
+		self.multisigs.get(&(multisig.clone(), call_hash))
+	}
+
+	/// Build the sorted list of signatories out of the `caller` and the `other_signatories`, and
+	/// check that the `threshold` can be met by them.
+	fn signatories(
+		caller: &T::AccountId,
+		other_signatories: Vec<T::AccountId>,
+		threshold: u32,
+	) -> Result<Vec<T::AccountId>, &'static str> {
+		let mut signatories = other_signatories;
+		signatories.push(caller.clone());
+		signatories.sort();
+
+		if signatories.windows(2).any(|pair| pair[0] == pair[1]) {
+			return Err("Signatories must be unique")
+		}
+		if threshold < 2 {
+			return Err("Threshold must be at least 2")
+		}
+		if threshold as usize > signatories.len() {
+			return Err("Threshold is above the number of signatories")
+		}
+		Ok(signatories)
+	}
+
+	/// Record the approval of `who` for the call with the hash `call_hash`, reserving the deposit
+	/// if this is the first approval. Returns the number of approvals of the call.
+	fn approve(
+		runtime: &mut T,
+		who: T::AccountId,
+		multisig: T::AccountId,
+		call_hash: Hash,
+	) -> Result<usize, &'static str>
+	where
+		T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		let key = (multisig.clone(), call_hash);
+		let multisig_pallet: &mut Self = runtime.pallet_mut();
+		let (approvals, event) = match multisig_pallet.multisigs.get_mut(&key) {
+			Some(pending) => {
+				if pending.approvals.contains(&who) {
+					return Err("Already approved")
+				}
+				pending.approvals.push(who.clone());
+				let event = Event::MultisigApproval { approving: who, multisig, call_hash };
+				(pending.approvals.len(), event)
+			},
+			None => {
+				let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+				balances.reserve(&who, T::DEPOSIT)?;
+
+				let multisig_pallet: &mut Self = runtime.pallet_mut();
+				let pending = Multisig {
+					depositor: who.clone(),
+					deposit: T::DEPOSIT,
+					approvals: vec![who.clone()],
+				};
+				multisig_pallet.multisigs.insert(key, pending);
+				(1, Event::NewMultisig { approving: who, multisig, call_hash })
+			},
+		};
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(event);
+		Ok(approvals)
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Runtime, RuntimeCall, RuntimeEvent};
+
+	#[test]
+	fn multi_account_id_does_not_depend_on_caller() {
+		// Arrange
+		let (_, alice, bob, charlie) = setup();
+
+		// Act
+		let from_alice =
+			Pallet::<Runtime>::signatories(&alice, vec![charlie.clone(), bob.clone()], 2);
+		let from_bob =
+			Pallet::<Runtime>::signatories(&bob, vec![alice.clone(), charlie.clone()], 2);
+
+		// Assert
+		let (from_alice, from_bob) = (from_alice.unwrap(), from_bob.unwrap());
+		assert_eq!(from_alice, from_bob);
+		assert_eq!(
+			Pallet::<Runtime>::multi_account_id(&from_alice, 2),
+			Pallet::<Runtime>::multi_account_id(&from_bob, 2)
+		);
+		assert_ne!(
+			Pallet::<Runtime>::multi_account_id(&from_alice, 2),
+			Pallet::<Runtime>::multi_account_id(&from_alice, 3)
+		);
+	}
+
+	#[test]
+	fn as_multi_executes_at_threshold() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let multisig = multisig_account(&alice, &bob, &charlie);
+		let call = transfer(&charlie, 30);
+		let call_hash = hash_of(&call);
+
+		// Act
+		let approve = Pallet::approve_as_multi(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			2,
+			vec![bob.clone(), charlie.clone()],
+			call_hash,
+		);
+		let reserved = runtime.balances.reserved_balance(&alice);
+		let execute = Pallet::as_multi(
+			&mut runtime,
+			Origin::Signed(bob.clone()),
+			2,
+			vec![alice.clone(), charlie.clone()],
+			Box::new(call),
+		);
+
+		// Assert
+		assert_eq!(approve, Ok(()));
+		assert_eq!(execute, Ok(()));
+		assert_eq!(reserved, 10);
+		assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+		assert_eq!(runtime.balances.balance(&alice), 100);
+		assert_eq!(runtime.balances.balance(&multisig), 20);
+		assert_eq!(runtime.balances.balance(&charlie), 30);
+		assert_eq!(runtime.multisig.multisig(&multisig, call_hash), None);
+		assert_eq!(
+			runtime.system.events(),
+			&[
+				RuntimeEvent::multisig(Event::NewMultisig {
+					approving: alice,
+					multisig: multisig.clone(),
+					call_hash
+				}),
+				RuntimeEvent::multisig(Event::MultisigApproval {
+					approving: bob.clone(),
+					multisig: multisig.clone(),
+					call_hash
+				}),
+				RuntimeEvent::multisig(Event::MultisigExecuted {
+					approving: bob,
+					multisig,
+					call_hash,
+					result: Ok(())
+				}),
+			]
+		);
+	}
+
+	#[test]
+	fn as_multi_waits_for_threshold() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let multisig = multisig_account(&alice, &bob, &charlie);
+		let call = transfer(&charlie, 30);
+
+		// Act
+		let res = Pallet::as_multi(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			2,
+			vec![bob.clone(), charlie.clone()],
+			Box::new(call.clone()),
+		);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&multisig), 50);
+		assert_eq!(
+			runtime.multisig.multisig(&multisig, hash_of(&call)),
+			Some(&Multisig { depositor: alice.clone(), deposit: 10, approvals: vec![alice] })
+		);
+	}
+
+	#[test]
+	fn approve_twice_fails() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let call_hash = hash_of(&transfer(&charlie, 30));
+		let others = vec![bob.clone(), charlie.clone()];
+		let _ = Pallet::approve_as_multi(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			2,
+			others.clone(),
+			call_hash,
+		);
+
+		// Act
+		let res = Pallet::approve_as_multi(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			2,
+			others,
+			call_hash,
+		);
+
+		// Assert
+		assert_eq!(res, Err("Already approved"));
+		assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+	}
+
+	#[test]
+	fn approve_invalid_signatories() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let call_hash = hash_of(&transfer(&charlie, 30));
+
+		// Act & Assert
+		assert_eq!(
+			Pallet::approve_as_multi(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				1,
+				vec![bob.clone()],
+				call_hash
+			),
+			Err("Threshold must be at least 2")
+		);
+		assert_eq!(
+			Pallet::approve_as_multi(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				3,
+				vec![bob.clone()],
+				call_hash
+			),
+			Err("Threshold is above the number of signatories")
+		);
+		assert_eq!(
+			Pallet::approve_as_multi(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				2,
+				vec![alice.clone(), bob],
+				call_hash
+			),
+			Err("Signatories must be unique")
+		);
+	}
+
+	#[test]
+	fn cancel_as_multi_success() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let multisig = multisig_account(&alice, &bob, &charlie);
+		let call_hash = hash_of(&transfer(&charlie, 30));
+		let others = vec![bob.clone(), charlie.clone()];
+		let _ = Pallet::approve_as_multi(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			2,
+			others.clone(),
+			call_hash,
+		);
+
+		// Act
+		let res = Pallet::cancel_as_multi(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			2,
+			others,
+			call_hash,
+		);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+		assert_eq!(runtime.balances.balance(&alice), 100);
+		assert_eq!(runtime.multisig.multisig(&multisig, call_hash), None);
+	}
+
+	#[test]
+	fn cancel_as_multi_error_not_depositor() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let call_hash = hash_of(&transfer(&charlie, 30));
+		let _ = Pallet::approve_as_multi(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			2,
+			vec![bob.clone(), charlie.clone()],
+			call_hash,
+		);
+
+		// Act
+		let res = Pallet::cancel_as_multi(
+			&mut runtime,
+			Origin::Signed(bob),
+			2,
+			vec![alice.clone(), charlie],
+			call_hash,
+		);
+
+		// Assert
+		assert_eq!(res, Err("Caller is not the depositor of the multisig operation"));
+		assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+	}
+
+	fn multisig_account(alice: &str, bob: &str, charlie: &str) -> String {
+		let signatories = [alice.to_string(), bob.to_string(), charlie.to_string()];
+		Pallet::<Runtime>::multi_account_id(&signatories, 2)
+	}
+
+	fn transfer(to: &str, amount: u128) -> RuntimeCall {
+		RuntimeCall::balances(balances::Call::transfer { to: to.to_string(), amount })
+	}
+
+	fn setup() -> (Runtime, String, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let charlie = String::from("charlie");
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&alice, 100);
+		let multisig = multisig_account(&alice, &bob, &charlie);
+		runtime.balances.set_balance(&multisig, 50);
+
+		(runtime, alice, bob, charlie)
+	}
+}
@@ -0,0 +1,466 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	support::{DispatchResult, Hooks, OnRuntimeUpgrade, ProvideInherent, ValidateUnsigned},
+	system::{ensure_signed, Config as SystemConfig},
+};
+
+pub trait Config: SystemConfig {
+	/// The identifier of a collection of items.
+	type CollectionId: Ord + Copy;
+	/// The identifier of an item, unique within its collection.
+	type ItemId: Ord + Copy;
+}
+
+/// The details of a collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectionDetails<AccountId> {
+	/// The account which created the collection, and which can mint its items.
+	pub owner: AccountId,
+	/// The number of items of the collection.
+	pub items: u32,
+}
+
+/// The details of an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemDetails<AccountId> {
+	/// The owner of the item.
+	pub owner: AccountId,
+	/// The account allowed to transfer the item on behalf of its owner, if any.
+	pub approved: Option<AccountId>,
+}
+
+/// This is the NFTs Module.
+/// It keeps track of non-fungible items: every item is unique, is owned by a single account, and
+/// belongs to a collection. Items can be transferred, and can be described by attributes.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The details of each collection.
+	collections: BTreeMap<T::CollectionId, CollectionDetails<T::AccountId>>,
+	/// The details of each item, by collection and item.
+	#[allow(clippy::type_complexity)]
+	items: BTreeMap<(T::CollectionId, T::ItemId), ItemDetails<T::AccountId>>,
+	/// The attributes of each item, by collection, item and key.
+	attributes: BTreeMap<(T::CollectionId, T::ItemId, String), String>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Create the collection `collection`, owned by the caller.
+	pub fn create_collection(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		collection: T::CollectionId,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		if self.collections.contains_key(&collection) {
+			return Err("Collection already exists")
+		}
+
+		self.collections
+			.insert(collection, CollectionDetails { owner: caller, items: 0 });
+		Ok(())
+	}
+
+	/// Create the item `item` of the collection `collection`, owned by `owner`.
+	/// This function fails if the caller is not the owner of the collection.
+	pub fn mint(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		owner: T::AccountId,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.ensure_collection_owner(collection, &caller)?;
+		if self.items.contains_key(&(collection, item)) {
+			return Err("Item already exists")
+		}
+
+		let details = self.collections.get_mut(&collection).ok_or("Unknown collection")?;
+		details.items = details.items.checked_add(1).ok_or("Too many items")?;
+		self.items.insert((collection, item), ItemDetails { owner, approved: None });
+		Ok(())
+	}
+
+	/// Destroy the item `item` of the collection `collection`, with its attributes.
+	/// This function fails if the caller is not the owner of the item.
+	pub fn burn(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		collection: T::CollectionId,
+		item: T::ItemId,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.ensure_item_owner(collection, item, &caller)?;
+
+		self.items.remove(&(collection, item));
+		self.attributes.retain(|(c, i, _), _| (*c, *i) != (collection, item));
+		if let Some(details) = self.collections.get_mut(&collection) {
+			details.items -= 1;
+		}
+		Ok(())
+	}
+
+	/// Transfer the item `item` of the collection `collection` to `dest`.
+	/// The caller must be the owner of the item, or the account approved by the owner. The
+	/// approval is cleared by the transfer.
+	pub fn transfer(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		dest: T::AccountId,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let details = self.items.get_mut(&(collection, item)).ok_or("Unknown item")?;
+		if details.owner != caller && details.approved.as_ref() != Some(&caller) {
+			return Err("Caller is not allowed to transfer the item")
+		}
+
+		*details = ItemDetails { owner: dest, approved: None };
+		Ok(())
+	}
+
+	/// Allow `delegate` to transfer the item `item` of the collection `collection`, replacing any
+	/// previous approval.
+	/// This function fails if the caller is not the owner of the item.
+	pub fn approve_transfer(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		delegate: T::AccountId,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.ensure_item_owner(collection, item, &caller)?;
+
+		if let Some(details) = self.items.get_mut(&(collection, item)) {
+			details.approved = Some(delegate);
+		}
+		Ok(())
+	}
+
+	/// Remove the approval to transfer the item `item` of the collection `collection`.
+	/// This function fails if the caller is not the owner of the item.
+	pub fn cancel_approval(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		collection: T::CollectionId,
+		item: T::ItemId,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.ensure_item_owner(collection, item, &caller)?;
+
+		if let Some(details) = self.items.get_mut(&(collection, item)) {
+			details.approved = None;
+		}
+		Ok(())
+	}
+
+	/// Set the attribute `key` of the item `item` of the collection `collection` to `value`.
+	/// This function fails if the caller is not the owner of the collection.
+	pub fn set_attribute(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		key: String,
+		value: String,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.ensure_collection_owner(collection, &caller)?;
+		self.item(collection, item).ok_or("Unknown item")?;
+
+		self.attributes.insert((collection, item, key), value);
+		Ok(())
+	}
+
+	/// Remove the attribute `key` of the item `item` of the collection `collection`.
+	/// This function fails if the caller is not the owner of the collection.
+	pub fn clear_attribute(
+		&mut self,
+		origin: T::RuntimeOrigin,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		key: String,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		self.ensure_collection_owner(collection, &caller)?;
+
+		self.attributes.remove(&(collection, item, key)).ok_or("Unknown attribute")?;
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the NFTs Pallet.
+	pub fn new() -> Self {
+		Self { collections: BTreeMap::new(), items: BTreeMap::new(), attributes: BTreeMap::new() }
+	}
+
+	/// Get the details of the collection `collection`, if it exists.
+	pub fn collection(
+		&self,
+		collection: T::CollectionId,
+	) -> Option<&CollectionDetails<T::AccountId>> {
+		self.collections.get(&collection)
+	}
+
+	/// Get the details of the item `item` of the collection `collection`, if it exists.
+	pub fn item(
+		&self,
+		collection: T::CollectionId,
+		item: T::ItemId,
+	) -> Option<&ItemDetails<T::AccountId>> {
+		self.items.get(&(collection, item))
+	}
+
+	/// Get the owner of the item `item` of the collection `collection`, if it exists.
+	pub fn owner(&self, collection: T::CollectionId, item: T::ItemId) -> Option<&T::AccountId> {
+		self.item(collection, item).map(|details| &details.owner)
+	}
+
+	/// Get the attribute `key` of the item `item` of the collection `collection`, if it is set.
+	pub fn attribute(
+		&self,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		key: &str,
+	) -> Option<&String> {
+		self.attributes.get(&(collection, item, key.to_string()))
+	}
+
+	/// Check that `who` is the owner of the collection `collection`.
+	fn ensure_collection_owner(
+		&self,
+		collection: T::CollectionId,
+		who: &T::AccountId,
+	) -> DispatchResult {
+		let details = self.collection(collection).ok_or("Unknown collection")?;
+		if &details.owner != who {
+			return Err("Caller is not the owner of the collection")
+		}
+		Ok(())
+	}
+
+	/// Check that `who` is the owner of the item `item` of the collection `collection`.
+	fn ensure_item_owner(
+		&self,
+		collection: T::CollectionId,
+		item: T::ItemId,
+		who: &T::AccountId,
+	) -> DispatchResult {
+		let owner = self.owner(collection, item).ok_or("Unknown item")?;
+		if owner != who {
+			return Err("Caller is not the owner of the item")
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::support::Origin;
+
+	struct TestConfig;
+
+	impl SystemConfig for TestConfig {
+		type AccountId = String;
+		type RuntimeOrigin = Origin<String>;
+		type RuntimeEvent = ();
+		type BlockNumber = u32;
+		type Nonce = u32;
+	}
+
+	impl Config for TestConfig {
+		type CollectionId = u32;
+		type ItemId = u32;
+	}
+
+	const CERTIFICATES: u32 = 1;
+
+	#[test]
+	fn create_collection() {
+		// Arrange
+		let (mut nfts, alice, bob) = setup();
+		// Act
+		let result = nfts.create_collection(Origin::Signed(bob.clone()), 2);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(nfts.collection(2), Some(&CollectionDetails { owner: bob.clone(), items: 0 }));
+		assert_eq!(
+			nfts.create_collection(Origin::Signed(bob), CERTIFICATES),
+			Err("Collection already exists")
+		);
+		assert_eq!(nfts.collection(CERTIFICATES).map(|details| &details.owner), Some(&alice));
+	}
+
+	#[test]
+	fn mint_item() {
+		// Arrange
+		let (mut nfts, alice, bob) = setup();
+		// Act
+		let result = nfts.mint(Origin::Signed(alice.clone()), CERTIFICATES, 0, bob.clone());
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(nfts.owner(CERTIFICATES, 0), Some(&bob));
+		assert_eq!(nfts.collection(CERTIFICATES).map(|details| details.items), Some(1));
+		assert_eq!(
+			nfts.mint(Origin::Signed(alice), CERTIFICATES, 0, bob.clone()),
+			Err("Item already exists")
+		);
+		assert_eq!(
+			nfts.mint(Origin::Signed(bob.clone()), CERTIFICATES, 1, bob),
+			Err("Caller is not the owner of the collection")
+		);
+	}
+
+	#[test]
+	fn burn_item() {
+		// Arrange
+		let (mut nfts, alice, bob) = setup();
+		let _ = nfts.mint(Origin::Signed(alice.clone()), CERTIFICATES, 0, bob.clone());
+		let _ = nfts.set_attribute(
+			Origin::Signed(alice.clone()),
+			CERTIFICATES,
+			0,
+			String::from("course"),
+			String::from("Rust"),
+		);
+		// Act
+		let not_owner = nfts.burn(Origin::Signed(alice), CERTIFICATES, 0);
+		let result = nfts.burn(Origin::Signed(bob), CERTIFICATES, 0);
+		// Assert
+		assert_eq!(not_owner, Err("Caller is not the owner of the item"));
+		assert_eq!(result, Ok(()));
+		assert_eq!(nfts.item(CERTIFICATES, 0), None);
+		assert_eq!(nfts.attribute(CERTIFICATES, 0, "course"), None);
+		assert_eq!(nfts.collection(CERTIFICATES).map(|details| details.items), Some(0));
+	}
+
+	#[test]
+	fn transfer_item() {
+		// Arrange
+		let (mut nfts, alice, bob) = setup();
+		let charlie = String::from("charlie");
+		let _ = nfts.mint(Origin::Signed(alice.clone()), CERTIFICATES, 0, bob.clone());
+		// Act
+		let not_owner =
+			nfts.transfer(Origin::Signed(alice.clone()), CERTIFICATES, 0, charlie.clone());
+		let result = nfts.transfer(Origin::Signed(bob.clone()), CERTIFICATES, 0, charlie.clone());
+		// Assert
+		assert_eq!(not_owner, Err("Caller is not allowed to transfer the item"));
+		assert_eq!(result, Ok(()));
+		assert_eq!(nfts.owner(CERTIFICATES, 0), Some(&charlie));
+		assert_eq!(nfts.transfer(Origin::Signed(bob), CERTIFICATES, 1, alice), Err("Unknown item"));
+	}
+
+	#[test]
+	fn transfer_approved_item() {
+		// Arrange
+		let (mut nfts, alice, bob) = setup();
+		let charlie = String::from("charlie");
+		let _ = nfts.mint(Origin::Signed(alice.clone()), CERTIFICATES, 0, bob.clone());
+		let _ = nfts.approve_transfer(Origin::Signed(bob.clone()), CERTIFICATES, 0, alice.clone());
+		// Act
+		let result = nfts.transfer(Origin::Signed(alice.clone()), CERTIFICATES, 0, charlie.clone());
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(
+			nfts.item(CERTIFICATES, 0),
+			Some(&ItemDetails { owner: charlie.clone(), approved: None })
+		);
+		assert_eq!(
+			nfts.transfer(Origin::Signed(alice), CERTIFICATES, 0, bob),
+			Err("Caller is not allowed to transfer the item")
+		);
+	}
+
+	#[test]
+	fn cancel_approval() {
+		// Arrange
+		let (mut nfts, alice, bob) = setup();
+		let _ = nfts.mint(Origin::Signed(alice.clone()), CERTIFICATES, 0, bob.clone());
+		let _ = nfts.approve_transfer(Origin::Signed(bob.clone()), CERTIFICATES, 0, alice.clone());
+		// Act
+		let result = nfts.cancel_approval(Origin::Signed(bob.clone()), CERTIFICATES, 0);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(
+			nfts.transfer(Origin::Signed(alice), CERTIFICATES, 0, bob),
+			Err("Caller is not allowed to transfer the item")
+		);
+	}
+
+	#[test]
+	fn set_and_clear_attribute() {
+		// Arrange
+		let (mut nfts, alice, bob) = setup();
+		let _ = nfts.mint(Origin::Signed(alice.clone()), CERTIFICATES, 0, bob.clone());
+		// Act
+		let result = nfts.set_attribute(
+			Origin::Signed(alice.clone()),
+			CERTIFICATES,
+			0,
+			String::from("course"),
+			String::from("Rust"),
+		);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(nfts.attribute(CERTIFICATES, 0, "course"), Some(&String::from("Rust")));
+		assert_eq!(
+			nfts.set_attribute(
+				Origin::Signed(bob),
+				CERTIFICATES,
+				0,
+				String::from("course"),
+				String::from("Go")
+			),
+			Err("Caller is not the owner of the collection")
+		);
+		assert_eq!(
+			nfts.clear_attribute(
+				Origin::Signed(alice.clone()),
+				CERTIFICATES,
+				0,
+				String::from("course")
+			),
+			Ok(())
+		);
+		assert_eq!(nfts.attribute(CERTIFICATES, 0, "course"), None);
+		assert_eq!(
+			nfts.clear_attribute(Origin::Signed(alice), CERTIFICATES, 0, String::from("course")),
+			Err("Unknown attribute")
+		);
+	}
+
+	fn setup() -> (
+		Pallet<TestConfig>,
+		<TestConfig as SystemConfig>::AccountId,
+		<TestConfig as SystemConfig>::AccountId,
+	) {
+		let mut nfts = Pallet::new();
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		nfts.create_collection(Origin::Signed(alice.clone()), CERTIFICATES)
+			.expect("the collection does not exist yet");
+
+		(nfts, alice, bob)
+	}
+}
@@ -1,6 +1,9 @@
 use std::{collections::BTreeMap, fmt::Debug};
 
-use crate::support::DispatchResult;
+use crate::{
+	support::{DispatchResult, Hooks, OnRuntimeUpgrade, ProvideInherent, ValidateUnsigned},
+	system::ensure_signed,
+};
 
 pub trait Config: crate::system::Config {
 	/// The type which repersents the content that can be claimed using this pallet.
@@ -11,7 +14,7 @@ pub trait Config: crate::system::Config {
 
 /// This is the Proof of Existence Module.
 /// It is a simple module that allows accounts to claim existence of some data.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
 	/// A simple storage map from content to the owner of that content.
 	/// Accounts can make multiple different claims, but each claim can only have one owner.
@@ -22,7 +25,8 @@ pub struct Pallet<T: Config> {
 impl<T: Config> Pallet<T> {
 	/// Create a new claim on behalf of the `caller`.
 	/// This function will return an error if someone already has claimed that content.
-	pub fn create_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
+	pub fn create_claim(&mut self, origin: T::RuntimeOrigin, claim: T::Content) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
 		match self.get_claim(&claim) {
 			Some(_) => Err("Claim already exists"),
 			None => {
@@ -35,7 +39,8 @@ impl<T: Config> Pallet<T> {
 	/// Revoke an existing claim on some content.
 	/// This function should only succeed if the caller is the owner of an existing claim.
 	/// It will return an error if the claim does not exist, or if the caller is not the owner.
-	pub fn revoke_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
+	pub fn revoke_claim(&mut self, origin: T::RuntimeOrigin, claim: T::Content) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
 		let claim_owner = self.get_claim(&claim).ok_or("Claim does not exist")?;
 
 		if claim_owner != &caller {
@@ -57,15 +62,34 @@ impl<T: Config> Pallet<T> {
 	}
 }
 
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::support::Origin;
 
 	struct TestConfig;
 
 	impl crate::system::Config for TestConfig {
 		type AccountId = &'static str;
 
+		type RuntimeOrigin = Origin<&'static str>;
+
+		type RuntimeEvent = ();
+
 		type BlockNumber = u32;
 
 		type Nonce = u32;
@@ -95,7 +119,7 @@ mod tests {
 		let content = "Lorem ipsum";
 
 		// Act
-		let res = poe.create_claim(alice, content);
+		let res = poe.create_claim(Origin::Signed(alice), content);
 		let claim_owner = poe.get_claim(&content);
 
 		// Assert
@@ -108,10 +132,10 @@ mod tests {
 		// Arrange
 		let (mut poe, alice, _) = setup();
 		let content = "Lorem ipsum";
-		let _ = poe.create_claim(alice, content);
+		let _ = poe.create_claim(Origin::Signed(alice), content);
 
 		// Act
-		let res = poe.create_claim(alice, content);
+		let res = poe.create_claim(Origin::Signed(alice), content);
 
 		// Assert
 		assert_eq!(res, Err("Claim already exists"));
@@ -122,10 +146,10 @@ mod tests {
 		// Arrange
 		let (mut poe, alice, bob) = setup();
 		let content = "Lorem ipsum";
-		let _ = poe.create_claim(bob, content);
+		let _ = poe.create_claim(Origin::Signed(bob), content);
 
 		// Act
-		let res = poe.create_claim(alice, content);
+		let res = poe.create_claim(Origin::Signed(alice), content);
 
 		// Assert
 		assert_eq!(res, Err("Claim already exists"));
@@ -136,10 +160,10 @@ mod tests {
 		// Arrange
 		let (mut poe, alice, _) = setup();
 		let content = "Lorem ipsum";
-		let _ = poe.create_claim(alice, content);
+		let _ = poe.create_claim(Origin::Signed(alice), content);
 
 		// Act
-		let res = poe.revoke_claim(alice, content);
+		let res = poe.revoke_claim(Origin::Signed(alice), content);
 		let claim_owner = poe.get_claim(&content);
 
 		// Assert
@@ -153,10 +177,10 @@ mod tests {
 		let (mut poe, alice, _) = setup();
 		let content_1 = "Lorem ipsum";
 		let content_2 = "Foo bar";
-		let _ = poe.create_claim(alice, content_1);
+		let _ = poe.create_claim(Origin::Signed(alice), content_1);
 
 		// Act
-		let res = poe.revoke_claim(alice, content_2);
+		let res = poe.revoke_claim(Origin::Signed(alice), content_2);
 
 		// Assert
 		assert_eq!(res, Err("Claim does not exist"));
@@ -167,10 +191,10 @@ mod tests {
 		// Arrange
 		let (mut poe, alice, bob) = setup();
 		let content = "Lorem ipsum";
-		let _ = poe.create_claim(alice, content);
+		let _ = poe.create_claim(Origin::Signed(alice), content);
 
 		// Act
-		let res = poe.revoke_claim(bob, content);
+		let res = poe.revoke_claim(Origin::Signed(bob), content);
 
 		// Assert
 		assert_eq!(res, Err("Caller is not the owner of the claim"));
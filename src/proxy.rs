@@ -0,0 +1,564 @@
+use std::{collections::BTreeMap, hash::Hash as StdHash};
+
+use num::{CheckedAdd, Zero};
+
+use crate::{
+	support::{
+		hash_of, CallMetadata, Dispatch, DispatchResult, GetCallMetadata, GetPallet, Hash, Hooks,
+		OnRuntimeUpgrade, Origin, ProvideInherent, ValidateUnsigned,
+	},
+	system::{self, ensure_signed},
+};
+
+pub trait Config:
+	system::Config<
+		BlockNumber: CheckedAdd + Ord,
+		RuntimeEvent: From<
+			Event<<Self as system::Config>::AccountId, <Self as system::Config>::BlockNumber>,
+		>,
+	> + Sized
+{
+	/// The type of the calls which a proxy can dispatch on behalf of its delegator.
+	type RuntimeCall: GetCallMetadata + StdHash;
+}
+
+/// The kinds of calls a proxy is allowed to make on behalf of its delegator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProxyType {
+	/// Any call.
+	Any,
+	/// Only transfers of balance.
+	TransferOnly,
+	/// Only the calls of the Proof of Existence Pallet.
+	ClaimsOnly,
+}
+
+impl ProxyType {
+	/// Check whether a proxy of this type may make the call described by `call`.
+	/// The pallet names are the names of the fields of the `Runtime` struct.
+	pub fn filter(&self, call: &CallMetadata) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::TransferOnly =>
+				call.pallet_name == "balances" && call.function_name == "transfer",
+			ProxyType::ClaimsOnly => call.pallet_name == "proof_of_existence",
+		}
+	}
+}
+
+/// A delegate registered by an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyDefinition<AccountId, BlockNumber> {
+	/// The account which can make calls on behalf of the delegator.
+	pub delegate: AccountId,
+	/// The kinds of calls the delegate can make.
+	pub proxy_type: ProxyType,
+	/// The number of blocks a call must be announced in advance before the delegate can make it.
+	/// With no delay, the delegate can make calls directly.
+	pub delay: BlockNumber,
+}
+
+/// A call announced by a delegate, to be made after the delay of its proxy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Announcement<AccountId, BlockNumber> {
+	/// The account on whose behalf the call will be made.
+	pub real: AccountId,
+	/// The hash of the announced call.
+	pub call_hash: Hash,
+	/// The block number at which the call was announced.
+	pub height: BlockNumber,
+}
+
+/// The events deposited by the Proxy Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId, BlockNumber> {
+	/// `delegate` was registered as a proxy of `delegator`.
+	ProxyAdded {
+		delegator: AccountId,
+		delegate: AccountId,
+		proxy_type: ProxyType,
+		delay: BlockNumber,
+	},
+	/// `delegate` was removed from the proxies of `delegator`.
+	ProxyRemoved { delegator: AccountId, delegate: AccountId, proxy_type: ProxyType },
+	/// `proxy` announced a call to be made on behalf of `real`.
+	Announced { real: AccountId, proxy: AccountId, call_hash: Hash },
+	/// A call was dispatched by a proxy, with the given result.
+	ProxyExecuted { result: DispatchResult },
+}
+
+/// This is the Proxy Module.
+/// It allows an account to register delegates, which can then dispatch calls on its behalf. The
+/// calls a delegate can make are restricted by its `ProxyType`, and can require an announcement a
+/// number of blocks in advance, giving the delegator time to react.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The proxies registered by each delegator.
+	#[allow(clippy::type_complexity)]
+	proxies: BTreeMap<T::AccountId, Vec<ProxyDefinition<T::AccountId, T::BlockNumber>>>,
+	/// The calls announced by each delegate.
+	#[allow(clippy::type_complexity)]
+	announcements: BTreeMap<T::AccountId, Vec<Announcement<T::AccountId, T::BlockNumber>>>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: Dispatch<Origin = T::RuntimeOrigin, Call = T::RuntimeCall>
+		+ GetPallet<Pallet<T>>
+		+ GetPallet<system::Pallet<T>>,
+{
+	/// Register `delegate` as a proxy of the caller, for the calls allowed by `proxy_type`.
+	/// With a non zero `delay`, the delegate must announce its calls `delay` blocks in advance.
+	pub fn add_proxy(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		delegate: T::AccountId,
+		proxy_type: ProxyType,
+		delay: T::BlockNumber,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		if caller == delegate {
+			return Err("Cannot add self as proxy")
+		}
+
+		let proxy: &mut Self = runtime.pallet_mut();
+		let proxies = proxy.proxies.entry(caller.clone()).or_default();
+		if proxies.iter().any(|p| p.delegate == delegate && p.proxy_type == proxy_type) {
+			return Err("Proxy already exists")
+		}
+		proxies.push(ProxyDefinition { delegate: delegate.clone(), proxy_type, delay });
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::ProxyAdded { delegator: caller, delegate, proxy_type, delay });
+		Ok(())
+	}
+
+	/// Remove `delegate` from the proxies of the caller with the type `proxy_type`.
+	pub fn remove_proxy(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		delegate: T::AccountId,
+		proxy_type: ProxyType,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let proxy: &mut Self = runtime.pallet_mut();
+		let proxies = proxy.proxies.get_mut(&caller).ok_or("Proxy not found")?;
+		let index = proxies
+			.iter()
+			.position(|p| p.delegate == delegate && p.proxy_type == proxy_type)
+			.ok_or("Proxy not found")?;
+		proxies.remove(index);
+		if proxies.is_empty() {
+			proxy.proxies.remove(&caller);
+		}
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::ProxyRemoved { delegator: caller, delegate, proxy_type });
+		Ok(())
+	}
+
+	/// Dispatch `call` on behalf of `real`, of which the caller is a proxy without delay.
+	/// The result of the call is recorded in a `ProxyExecuted` event.
+	#[allow(clippy::boxed_local)]
+	pub fn proxy(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		real: T::AccountId,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let proxy: &Self = runtime.pallet();
+		let definition = proxy.find_proxy(&real, &caller, &call.call_metadata())?;
+		if !definition.delay.is_zero() {
+			return Err("Proxy call must be announced")
+		}
+
+		Self::do_proxy(runtime, real, *call);
+		Ok(())
+	}
+
+	/// Announce that the caller, a proxy of `real`, will dispatch the call with the hash
+	/// `call_hash` on its behalf, once the delay of the proxy has passed.
+	pub fn announce(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		real: T::AccountId,
+		call_hash: Hash,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		let height = system.block_number();
+
+		let proxy: &mut Self = runtime.pallet_mut();
+		let is_proxy = proxy.proxies(&real).iter().any(|p| p.delegate == caller);
+		if !is_proxy {
+			return Err("Not a proxy")
+		}
+		let announcement = Announcement { real: real.clone(), call_hash, height };
+		proxy.announcements.entry(caller.clone()).or_default().push(announcement);
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::Announced { real, proxy: caller, call_hash });
+		Ok(())
+	}
+
+	/// Dispatch the announced `call` on behalf of `real`, of which the caller is a proxy.
+	/// This function fails if the call was not announced by the caller, or if the delay of the
+	/// proxy has not passed yet.
+	#[allow(clippy::boxed_local)]
+	pub fn proxy_announced(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		real: T::AccountId,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let call_hash = hash_of(&call);
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		let now = system.block_number();
+
+		let proxy: &mut Self = runtime.pallet_mut();
+		let delay = proxy.find_proxy(&real, &caller, &call.call_metadata())?.delay;
+		let announcements = proxy.announcements.get_mut(&caller).ok_or("Announcement not found")?;
+		let index = announcements
+			.iter()
+			.position(|a| a.real == real && a.call_hash == call_hash)
+			.ok_or("Announcement not found")?;
+		let due = announcements[index].height.checked_add(&delay).ok_or("Block number overflow")?;
+		if now < due {
+			return Err("Announcement delay has not passed")
+		}
+		announcements.remove(index);
+		if announcements.is_empty() {
+			proxy.announcements.remove(&caller);
+		}
+
+		Self::do_proxy(runtime, real, *call);
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Proxy Pallet.
+	pub fn new() -> Self {
+		Self { proxies: BTreeMap::new(), announcements: BTreeMap::new() }
+	}
+
+	/// Get the proxies registered by `delegator`.
+	pub fn proxies(
+		&self,
+		delegator: &T::AccountId,
+	) -> &[ProxyDefinition<T::AccountId, T::BlockNumber>] {
+		self.proxies.get(delegator).map(Vec::as_slice).unwrap_or(&[])
+	}
+
+	/// Get the calls announced by `delegate`.
+	pub fn announcements(
+		&self,
+		delegate: &T::AccountId,
+	) -> &[Announcement<T::AccountId, T::BlockNumber>] {
+		self.announcements.get(delegate).map(Vec::as_slice).unwrap_or(&[])
+	}
+
+	/// Find the proxy of `real` registered for `delegate` which allows the call described by
+	/// `call`. If there are many, the one with the shortest delay is returned.
+	fn find_proxy(
+		&self,
+		real: &T::AccountId,
+		delegate: &T::AccountId,
+		call: &CallMetadata,
+	) -> Result<&ProxyDefinition<T::AccountId, T::BlockNumber>, &'static str> {
+		let mut proxies = self.proxies(real).iter().filter(|p| &p.delegate == delegate).peekable();
+		if proxies.peek().is_none() {
+			return Err("Not a proxy")
+		}
+		proxies
+			.filter(|p| p.proxy_type.filter(call))
+			.min_by_key(|p| p.delay)
+			.ok_or("Call is not allowed by the proxy type")
+	}
+
+	/// Dispatch `call` on behalf of `real`, recording the result in a `ProxyExecuted` event.
+	fn do_proxy(runtime: &mut T, real: T::AccountId, call: T::RuntimeCall)
+	where
+		T: Dispatch<Origin = T::RuntimeOrigin, Call = T::RuntimeCall>
+			+ GetPallet<system::Pallet<T>>,
+	{
+		let result = runtime.dispatch(Origin::Signed(real).into(), call);
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::ProxyExecuted { result });
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{balances, proof_of_existence, Runtime, RuntimeCall, RuntimeEvent};
+
+	#[test]
+	fn call_metadata() {
+		// Arrange
+		let call = transfer("bob", 10);
+
+		// Act
+		let metadata = call.call_metadata();
+
+		// Assert
+		assert_eq!(metadata, CallMetadata { pallet_name: "balances", function_name: "transfer" });
+	}
+
+	#[test]
+	fn add_proxy_success() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+
+		// Act
+		let res = Pallet::add_proxy(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			ProxyType::Any,
+			0,
+		);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(
+			runtime.proxy.proxies(&alice),
+			&[ProxyDefinition { delegate: bob.clone(), proxy_type: ProxyType::Any, delay: 0 }]
+		);
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::proxy(Event::ProxyAdded {
+				delegator: alice,
+				delegate: bob,
+				proxy_type: ProxyType::Any,
+				delay: 0
+			})]
+		);
+	}
+
+	#[test]
+	fn add_proxy_errors() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::add_proxy(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			ProxyType::Any,
+			0,
+		);
+
+		// Act & Assert
+		assert_eq!(
+			Pallet::add_proxy(&mut runtime, Origin::Signed(alice.clone()), bob, ProxyType::Any, 0),
+			Err("Proxy already exists")
+		);
+		assert_eq!(
+			Pallet::add_proxy(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				alice,
+				ProxyType::Any,
+				0
+			),
+			Err("Cannot add self as proxy")
+		);
+	}
+
+	#[test]
+	fn remove_proxy_success() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::add_proxy(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			ProxyType::Any,
+			0,
+		);
+
+		// Act
+		let res = Pallet::remove_proxy(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			ProxyType::Any,
+		);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert!(runtime.proxy.proxies(&alice).is_empty());
+		assert_eq!(
+			Pallet::remove_proxy(&mut runtime, Origin::Signed(alice), bob, ProxyType::Any),
+			Err("Proxy not found")
+		);
+	}
+
+	#[test]
+	fn proxy_dispatches_as_delegator() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::add_proxy(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			ProxyType::TransferOnly,
+			0,
+		);
+		runtime.system.reset_events();
+
+		// Act
+		let res = Pallet::proxy(
+			&mut runtime,
+			Origin::Signed(bob.clone()),
+			alice.clone(),
+			Box::new(transfer(&bob, 30)),
+		);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 70);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::proxy(Event::ProxyExecuted { result: Ok(()) })]
+		);
+	}
+
+	#[test]
+	fn proxy_errors() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::add_proxy(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			ProxyType::ClaimsOnly,
+			0,
+		);
+		let charlie = String::from("charlie");
+
+		// Act & Assert
+		assert_eq!(
+			Pallet::proxy(
+				&mut runtime,
+				Origin::Signed(bob.clone()),
+				alice.clone(),
+				Box::new(transfer(&bob, 30))
+			),
+			Err("Call is not allowed by the proxy type")
+		);
+		assert_eq!(
+			Pallet::proxy(
+				&mut runtime,
+				Origin::Signed(charlie),
+				alice.clone(),
+				Box::new(transfer(&bob, 30))
+			),
+			Err("Not a proxy")
+		);
+		assert_eq!(runtime.balances.balance(&alice), 100);
+
+		let claim = RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+			claim: "Alice's document",
+		});
+		assert_eq!(
+			Pallet::proxy(&mut runtime, Origin::Signed(bob), alice.clone(), Box::new(claim)),
+			Ok(())
+		);
+		assert_eq!(runtime.proof_of_existence.get_claim(&"Alice's document"), Some(&alice));
+	}
+
+	#[test]
+	fn proxy_with_delay_requires_announcement() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::add_proxy(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			ProxyType::Any,
+			2,
+		);
+		let call = transfer(&bob, 30);
+
+		// Act
+		let direct = Pallet::proxy(
+			&mut runtime,
+			Origin::Signed(bob.clone()),
+			alice.clone(),
+			Box::new(call.clone()),
+		);
+		let unannounced = Pallet::proxy_announced(
+			&mut runtime,
+			Origin::Signed(bob.clone()),
+			alice.clone(),
+			Box::new(call.clone()),
+		);
+		let announce = Pallet::announce(
+			&mut runtime,
+			Origin::Signed(bob.clone()),
+			alice.clone(),
+			hash_of(&call),
+		);
+		runtime.system.inc_block_number();
+		let early = Pallet::proxy_announced(
+			&mut runtime,
+			Origin::Signed(bob.clone()),
+			alice.clone(),
+			Box::new(call.clone()),
+		);
+		runtime.system.inc_block_number();
+		let due = Pallet::proxy_announced(
+			&mut runtime,
+			Origin::Signed(bob.clone()),
+			alice.clone(),
+			Box::new(call),
+		);
+
+		// Assert
+		assert_eq!(direct, Err("Proxy call must be announced"));
+		assert_eq!(unannounced, Err("Announcement not found"));
+		assert_eq!(announce, Ok(()));
+		assert_eq!(early, Err("Announcement delay has not passed"));
+		assert_eq!(due, Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 70);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+		assert!(runtime.proxy.announcements(&bob).is_empty());
+	}
+
+	fn transfer(to: &str, amount: u128) -> RuntimeCall {
+		RuntimeCall::balances(balances::Call::transfer { to: to.to_string(), amount })
+	}
+
+	fn setup() -> (Runtime, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&alice, 100);
+
+		(runtime, alice, bob)
+	}
+}
@@ -0,0 +1,159 @@
+use std::{collections::VecDeque, hash::Hash as StdHash};
+
+use crate::{
+	support::{
+		hash_of, GetPallet, Hash, Hooks, OnRuntimeUpgrade, ProvideInherent, Randomness,
+		ValidateUnsigned,
+	},
+	system,
+};
+
+/// The number of blocks whose seed is mixed into the random values.
+pub const RANDOM_MATERIAL_LEN: usize = 81;
+
+pub trait Config: system::Config<BlockNumber: StdHash> {}
+
+/// This is the Randomness Module.
+/// It collects a seed for each of the last `RANDOM_MATERIAL_LEN` blocks, derived from the hash of
+/// the parent of the block and its number, and mixes them into random values. See [`Randomness`]
+/// for the limits of this source of randomness.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The seeds of the last blocks, oldest first.
+	random_material: VecDeque<Hash>,
+	_config: std::marker::PhantomData<T>,
+}
+
+// The Randomness Pallet has no callable function, but the runtime expects a `Call` from every
+// pallet.
+#[macros::call]
+impl<T: Config> Pallet<T> {}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Randomness Pallet.
+	pub fn new() -> Self {
+		Self { random_material: VecDeque::new(), _config: std::marker::PhantomData }
+	}
+
+	/// Get a random value for `subject`, mixing the seeds of the last blocks.
+	pub fn random(&self, subject: &[u8]) -> Hash {
+		hash_of(&(subject, &self.random_material))
+	}
+
+	/// Get the seeds of the last blocks, oldest first.
+	pub fn random_material(&self) -> &VecDeque<Hash> {
+		&self.random_material
+	}
+}
+
+impl<T> Randomness<T> for Pallet<T>
+where
+	T: Config + GetPallet<Pallet<T>>,
+{
+	fn random(runtime: &T, subject: &[u8]) -> Hash {
+		let randomness: &Self = runtime.pallet();
+		randomness.random(subject)
+	}
+}
+
+impl<T> Hooks<T, T::BlockNumber> for Pallet<T>
+where
+	T: Config + GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>>,
+{
+	/// Record the seed of the block `n`, dropping the oldest seed once there are
+	/// `RANDOM_MATERIAL_LEN` of them.
+	fn on_initialize(runtime: &mut T, n: T::BlockNumber) {
+		let system: &system::Pallet<T> = runtime.pallet();
+		let seed = hash_of(&(system.parent_hash(), n));
+
+		let randomness: &mut Self = runtime.pallet_mut();
+		if randomness.random_material.len() == RANDOM_MATERIAL_LEN {
+			randomness.random_material.pop_front();
+		}
+		randomness.random_material.push_back(seed);
+	}
+}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		support::{Extrinsic, Header},
+		timestamp, types, Runtime, RuntimeCall,
+	};
+
+	#[test]
+	fn random_depends_on_subject_and_blocks() {
+		// Arrange
+		let mut runtime = Runtime::new();
+		let before = runtime.randomness.random(b"lottery");
+
+		// Act
+		execute_block(&mut runtime, 7);
+
+		// Assert
+		let after = runtime.randomness.random(b"lottery");
+		assert_ne!(before, after);
+		assert_ne!(after, runtime.randomness.random(b"validators"));
+		assert_eq!(after, <Pallet<Runtime> as Randomness<Runtime>>::random(&runtime, b"lottery"));
+	}
+
+	#[test]
+	fn random_is_deterministic() {
+		// Arrange
+		let mut first = Runtime::new();
+		let mut second = Runtime::new();
+
+		// Act
+		execute_block(&mut first, 7);
+		execute_block(&mut second, 7);
+
+		// Assert
+		assert_eq!(first.randomness.random(b"lottery"), second.randomness.random(b"lottery"));
+		execute_block(&mut first, 8);
+		execute_block(&mut second, 9);
+		assert_ne!(first.randomness.random(b"lottery"), second.randomness.random(b"lottery"));
+	}
+
+	#[test]
+	fn random_material_is_bounded() {
+		// Arrange
+		let mut runtime = Runtime::new();
+
+		// Act
+		for parent_hash in 0..100 {
+			execute_block(&mut runtime, parent_hash);
+		}
+
+		// Assert
+		let material = runtime.randomness.random_material();
+		assert_eq!(material.len(), RANDOM_MATERIAL_LEN);
+		assert_eq!(material.back(), Some(&hash_of(&(99u64, 100u32))));
+	}
+
+	fn execute_block(runtime: &mut Runtime, parent_hash: Hash) {
+		let block_number = runtime.system.block_number() + 1;
+		let block = types::Block {
+			header: Header { parent_hash, block_number },
+			extrinsics: vec![Extrinsic {
+				caller: None,
+				call: RuntimeCall::timestamp(timestamp::Call::set {
+					now: types::Moment::from(block_number) * 1_000,
+				}),
+			}],
+		};
+		runtime.execute_block(block).expect("empty blocks are valid");
+	}
+}
@@ -0,0 +1,384 @@
+use std::collections::BTreeMap;
+
+use num::{CheckedAdd, One, Zero};
+
+use crate::{
+	support::{
+		Dispatch, DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, Origin, ProvideInherent,
+		ValidateUnsigned,
+	},
+	system,
+};
+
+pub trait Config:
+	system::Config<
+		BlockNumber: CheckedAdd + Ord,
+		RuntimeEvent: From<Event<<Self as system::Config>::BlockNumber>>,
+	> + Sized
+{
+	/// The maximum number of scheduled calls dispatched at the beginning of a block. The calls
+	/// above the limit are postponed to the next block.
+	const MAX_SCHEDULED_PER_BLOCK: u32;
+	/// The type of the calls which can be scheduled.
+	type RuntimeCall: Clone;
+}
+
+/// The identifier of a scheduled task.
+pub type TaskId = u32;
+
+/// A call scheduled for a future block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheduled<AccountId, BlockNumber, Call> {
+	/// The identifier of the task, used to cancel it.
+	pub id: TaskId,
+	/// The origin which scheduled the call, and which the call is dispatched with.
+	pub origin: Origin<AccountId>,
+	/// The call to dispatch.
+	pub call: Call,
+	/// For a periodic task, the number of blocks between two executions, and the number of
+	/// executions left, including the next one.
+	pub periodic: Option<(BlockNumber, u32)>,
+}
+
+/// The events deposited by the Scheduler Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<BlockNumber> {
+	/// The task `id` was scheduled for the block `when`.
+	Scheduled { when: BlockNumber, id: TaskId },
+	/// The task `id`, scheduled for the block `when`, was cancelled.
+	Canceled { when: BlockNumber, id: TaskId },
+	/// The call of the task `id` was dispatched, with the given result.
+	Dispatched { id: TaskId, result: DispatchResult },
+}
+
+/// This is the Scheduler Module.
+/// It allows calls to be dispatched at a future block, once or periodically. The calls due at a
+/// block are dispatched at the beginning of that block, before its extrinsics.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The tasks scheduled for each block, in the order they will be dispatched.
+	#[allow(clippy::type_complexity)]
+	agenda: BTreeMap<T::BlockNumber, Vec<Scheduled<T::AccountId, T::BlockNumber, T::RuntimeCall>>>,
+	/// The identifier of the next scheduled task.
+	next_id: TaskId,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>>,
+{
+	/// Schedule `call` to be dispatched with the origin of the caller at the block `when`.
+	/// A `periodic` task is dispatched again every `period` blocks, for `count` executions in
+	/// total.
+	#[allow(clippy::boxed_local)]
+	pub fn schedule(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		when: T::BlockNumber,
+		periodic: Option<(T::BlockNumber, u32)>,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let origin: Origin<T::AccountId> = origin.into();
+		if origin == Origin::None {
+			return Err("Origin must be signed or root")
+		}
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		if when <= system.block_number() {
+			return Err("Scheduled block is in the past")
+		}
+		if let Some((period, count)) = periodic {
+			if period.is_zero() || count == 0 {
+				return Err("Periodic task must have a non zero period and count")
+			}
+		}
+
+		let scheduler: &mut Self = runtime.pallet_mut();
+		let id = scheduler.next_id;
+		scheduler.next_id = id.checked_add(1).ok_or("Task id overflow")?;
+		let task = Scheduled { id, origin, call: *call, periodic };
+		scheduler.agenda.entry(when).or_default().push(task);
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::Scheduled { when, id });
+		Ok(())
+	}
+
+	/// Cancel the scheduled task `id`, including the next executions of a periodic task.
+	/// This function fails if the caller is neither the origin which scheduled the task nor root.
+	pub fn cancel(runtime: &mut T, origin: T::RuntimeOrigin, id: TaskId) -> DispatchResult {
+		let origin: Origin<T::AccountId> = origin.into();
+
+		let scheduler: &mut Self = runtime.pallet_mut();
+		let (when, index) = scheduler.find_task(id).ok_or("Scheduled task not found")?;
+		let agenda = scheduler.agenda.get_mut(&when).expect("the task was found in this agenda");
+		if origin != Origin::Root && origin != agenda[index].origin {
+			return Err("Origin did not schedule the task")
+		}
+		agenda.remove(index);
+		if agenda.is_empty() {
+			scheduler.agenda.remove(&when);
+		}
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::Canceled { when, id });
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Scheduler Pallet.
+	pub fn new() -> Self {
+		Self { agenda: BTreeMap::new(), next_id: 0 }
+	}
+
+	/// Get the tasks scheduled for the block `when`.
+	pub fn agenda(
+		&self,
+		when: &T::BlockNumber,
+	) -> &[Scheduled<T::AccountId, T::BlockNumber, T::RuntimeCall>] {
+		self.agenda.get(when).map(Vec::as_slice).unwrap_or(&[])
+	}
+
+	/// Find the block and the position in the agenda of that block of the task `id`.
+	fn find_task(&self, id: TaskId) -> Option<(T::BlockNumber, usize)> {
+		self.agenda.iter().find_map(|(when, tasks)| {
+			tasks.iter().position(|task| task.id == id).map(|index| (*when, index))
+		})
+	}
+}
+
+impl<T> Hooks<T, T::BlockNumber> for Pallet<T>
+where
+	T: Config
+		+ Dispatch<Origin = T::RuntimeOrigin, Call = T::RuntimeCall>
+		+ GetPallet<Pallet<T>>
+		+ GetPallet<system::Pallet<T>>,
+{
+	/// Dispatch the calls scheduled for the block `n`, up to `MAX_SCHEDULED_PER_BLOCK`.
+	fn on_initialize(runtime: &mut T, n: T::BlockNumber) {
+		let scheduler: &mut Self = runtime.pallet_mut();
+		let mut tasks = scheduler.agenda.remove(&n).unwrap_or_default();
+		let max = T::MAX_SCHEDULED_PER_BLOCK as usize;
+		if tasks.len() > max {
+			// The tasks above the limit go first in the next block, before the tasks scheduled
+			// for it.
+			let postponed = tasks.split_off(max);
+			if let Some(next) = n.checked_add(&T::BlockNumber::one()) {
+				scheduler.agenda.entry(next).or_default().splice(0..0, postponed);
+			}
+		}
+
+		for Scheduled { id, origin, call, periodic } in tasks {
+			let next = match periodic {
+				Some((period, count)) if count > 1 => n.checked_add(&period).map(|when| {
+					let periodic = Some((period, count - 1));
+					(when, Scheduled { id, origin: origin.clone(), call: call.clone(), periodic })
+				}),
+				_ => None,
+			};
+			if let Some((when, task)) = next {
+				let scheduler: &mut Self = runtime.pallet_mut();
+				scheduler.agenda.entry(when).or_default().push(task);
+			}
+
+			let result = runtime.dispatch(origin.into(), call);
+
+			let system: &mut system::Pallet<T> = runtime.pallet_mut();
+			system.deposit_event(Event::Dispatched { id, result });
+		}
+	}
+}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		balances,
+		support::{Extrinsic, Header},
+		timestamp, types, Runtime, RuntimeCall, RuntimeEvent,
+	};
+
+	#[test]
+	fn scheduled_call_is_dispatched_at_block() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let res = Pallet::schedule(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			2,
+			None,
+			Box::new(transfer(&bob, 10)),
+		);
+
+		// Act
+		execute_blocks(&mut runtime, 1);
+		let bob_at_block_1 = runtime.balances.balance(&bob);
+		execute_blocks(&mut runtime, 1);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(bob_at_block_1, 0);
+		assert_eq!(runtime.balances.balance(&alice), 90);
+		assert_eq!(runtime.balances.balance(&bob), 10);
+		assert!(runtime.scheduler.agenda(&2).is_empty());
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::scheduler(Event::Dispatched { id: 0, result: Ok(()) })]
+		);
+	}
+
+	#[test]
+	fn schedule_errors() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		execute_blocks(&mut runtime, 1);
+
+		// Act & Assert
+		assert_eq!(
+			Pallet::schedule(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				1,
+				None,
+				Box::new(transfer(&bob, 10))
+			),
+			Err("Scheduled block is in the past")
+		);
+		assert_eq!(
+			Pallet::schedule(&mut runtime, Origin::None, 2, None, Box::new(transfer(&bob, 10))),
+			Err("Origin must be signed or root")
+		);
+		assert_eq!(
+			Pallet::schedule(
+				&mut runtime,
+				Origin::Signed(alice),
+				2,
+				Some((0, 2)),
+				Box::new(transfer(&bob, 10))
+			),
+			Err("Periodic task must have a non zero period and count")
+		);
+	}
+
+	#[test]
+	fn periodic_call_is_repeated() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::schedule(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			1,
+			Some((2, 3)),
+			Box::new(transfer(&bob, 10)),
+		);
+
+		// Act
+		execute_blocks(&mut runtime, 4);
+		let bob_at_block_4 = runtime.balances.balance(&bob);
+		execute_blocks(&mut runtime, 3);
+
+		// Assert
+		assert_eq!(bob_at_block_4, 20);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+		assert_eq!(runtime.balances.balance(&alice), 70);
+	}
+
+	#[test]
+	fn cancel_success() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::schedule(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			2,
+			None,
+			Box::new(transfer(&bob, 10)),
+		);
+		let _ = Pallet::schedule(&mut runtime, Origin::Root, 2, None, Box::new(transfer(&bob, 10)));
+
+		// Act
+		let by_bob = Pallet::cancel(&mut runtime, Origin::Signed(bob.clone()), 0);
+		let by_alice = Pallet::cancel(&mut runtime, Origin::Signed(alice.clone()), 0);
+		let by_root = Pallet::cancel(&mut runtime, Origin::Root, 1);
+		execute_blocks(&mut runtime, 2);
+
+		// Assert
+		assert_eq!(by_bob, Err("Origin did not schedule the task"));
+		assert_eq!(by_alice, Ok(()));
+		assert_eq!(by_root, Ok(()));
+		assert_eq!(runtime.balances.balance(&bob), 0);
+		assert_eq!(
+			Pallet::cancel(&mut runtime, Origin::Signed(alice), 0),
+			Err("Scheduled task not found")
+		);
+	}
+
+	#[test]
+	fn calls_above_limit_are_postponed() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		for _ in 0..6 {
+			let _ = Pallet::schedule(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				1,
+				None,
+				Box::new(transfer(&bob, 10)),
+			);
+		}
+
+		// Act
+		execute_blocks(&mut runtime, 1);
+		let bob_at_block_1 = runtime.balances.balance(&bob);
+		let postponed = runtime.scheduler.agenda(&2).iter().map(|task| task.id).collect::<Vec<_>>();
+		execute_blocks(&mut runtime, 1);
+
+		// Assert
+		assert_eq!(bob_at_block_1, 50);
+		assert_eq!(postponed, vec![5]);
+		assert_eq!(runtime.balances.balance(&bob), 60);
+	}
+
+	fn transfer(to: &str, amount: u128) -> RuntimeCall {
+		RuntimeCall::balances(balances::Call::transfer { to: to.to_string(), amount })
+	}
+
+	fn execute_blocks(runtime: &mut Runtime, count: u32) {
+		for _ in 0..count {
+			let block_number = runtime.system.block_number() + 1;
+			let block = types::Block {
+				header: Header { parent_hash: 0, block_number },
+				extrinsics: vec![Extrinsic {
+					caller: None,
+					call: RuntimeCall::timestamp(timestamp::Call::set {
+						now: types::Moment::from(block_number) * 1_000,
+					}),
+				}],
+			};
+			runtime.execute_block(block).expect("empty blocks are valid");
+		}
+	}
+
+	fn setup() -> (Runtime, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&alice, 100);
+
+		(runtime, alice, bob)
+	}
+}
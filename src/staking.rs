@@ -0,0 +1,578 @@
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	hash::Hash as StdHash,
+};
+
+use num::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Zero};
+
+use crate::{
+	balances::{self, LockIdentifier},
+	support::{
+		hash_of, DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, ProvideInherent, Randomness,
+		ValidateUnsigned,
+	},
+	system::{self, ensure_signed},
+};
+
+/// The identifier of the lock of the bonded balance.
+pub const STAKING_ID: LockIdentifier = *b"staking ";
+
+/// The index of an era.
+pub type EraIndex = u32;
+
+pub trait Config:
+	balances::Config<Balance: CheckedMul + CheckedDiv>
+	+ system::Config<
+		AccountId: StdHash,
+		BlockNumber: CheckedAdd + Ord,
+		RuntimeEvent: From<
+			Event<<Self as system::Config>::AccountId, <Self as balances::Config>::Balance>,
+		>,
+	> + Sized
+{
+	/// The number of blocks of an era. Rewards are paid at the end of every era.
+	const ERA_LENGTH: Self::BlockNumber;
+	/// The number of blocks an unbonded balance stays locked before it can be withdrawn.
+	const BONDING_DURATION: Self::BlockNumber;
+	/// The balance minted at the end of every era, and shared between the stakers.
+	const ERA_REWARD: Self::Balance;
+	/// The maximum number of validators of an era. When more stakers want to validate, the
+	/// validators of the era are drawn at random among them.
+	const MAX_VALIDATORS: u32;
+	/// The source of randomness used to draw the validators.
+	type Randomness: Randomness<Self>;
+}
+
+/// The bonded balance of a staker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakingLedger<Balance, BlockNumber> {
+	/// The balance which is staked.
+	pub active: Balance,
+	/// The balances being unbonded, with the block at which each of them can be withdrawn.
+	pub unlocking: Vec<(Balance, BlockNumber)>,
+}
+
+impl<Balance: CheckedAdd + Zero + Copy, BlockNumber> StakingLedger<Balance, BlockNumber> {
+	/// The whole balance of the ledger, including the balance being unbonded, which stays locked.
+	pub fn total(&self) -> Option<Balance> {
+		self.unlocking
+			.iter()
+			.try_fold(self.active, |total, (value, _)| total.checked_add(value))
+	}
+}
+
+/// The events deposited by the Staking Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId, Balance> {
+	/// `stash` bonded `amount`.
+	Bonded { stash: AccountId, amount: Balance },
+	/// `stash` started to unbond `amount`.
+	Unbonded { stash: AccountId, amount: Balance },
+	/// `stash` withdrew `amount` of unbonded balance.
+	Withdrawn { stash: AccountId, amount: Balance },
+	/// `stash` was rewarded `amount` for staking during the era.
+	Rewarded { stash: AccountId, amount: Balance },
+	/// The era `era_index` ended, and its rewards were paid.
+	EraPaid { era_index: EraIndex },
+}
+
+/// This is the Staking Module.
+/// It allows accounts to bond a part of their balance, which stays locked in the Balances Pallet,
+/// and either validate or nominate validators with it. At the end of every era, a reward is
+/// minted and shared between the validators of the era and their nominators, in proportion to
+/// their bonded balance. Unbonded balance stays locked for `BONDING_DURATION` blocks.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The bonded balance of each staker.
+	#[allow(clippy::type_complexity)]
+	ledgers: BTreeMap<T::AccountId, StakingLedger<T::Balance, T::BlockNumber>>,
+	/// The stakers who want to validate.
+	validators: BTreeSet<T::AccountId>,
+	/// The validators nominated by each nominator.
+	nominators: BTreeMap<T::AccountId, Vec<T::AccountId>>,
+	/// The index of the current era.
+	current_era: EraIndex,
+	/// The block at which the current era ends.
+	era_end: T::BlockNumber,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+{
+	/// Bond `amount` of the free balance of the caller, in addition to what it already bonded.
+	pub fn bond(runtime: &mut T, origin: T::RuntimeOrigin, amount: T::Balance) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let staking: &mut Self = runtime.pallet_mut();
+		let mut ledger = staking.ledger(&caller);
+		ledger.active = ledger.active.checked_add(&amount).ok_or("Fund overflow.")?;
+		let total = ledger.total().ok_or("Fund overflow.")?;
+
+		let balances: &balances::Pallet<T> = runtime.pallet();
+		if balances.balance(&caller) < total {
+			return Err("Not enough funds to bond.")
+		}
+
+		Self::update_ledger(runtime, &caller, ledger);
+		Self::deposit_event(runtime, Event::Bonded { stash: caller, amount });
+		Ok(())
+	}
+
+	/// Start to unbond `amount` of the balance bonded by the caller. The balance can be withdrawn
+	/// with `withdraw_unbonded` after `BONDING_DURATION` blocks.
+	pub fn unbond(runtime: &mut T, origin: T::RuntimeOrigin, amount: T::Balance) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		let unlock_at = system
+			.block_number()
+			.checked_add(&T::BONDING_DURATION)
+			.ok_or("Block number overflow")?;
+
+		let staking: &mut Self = runtime.pallet_mut();
+		let mut ledger = staking.ledger(&caller);
+		ledger.active = ledger.active.checked_sub(&amount).ok_or("Not enough bonded funds.")?;
+		ledger.unlocking.push((amount, unlock_at));
+
+		Self::update_ledger(runtime, &caller, ledger);
+		Self::deposit_event(runtime, Event::Unbonded { stash: caller, amount });
+		Ok(())
+	}
+
+	/// Unlock the balances of the caller which finished unbonding.
+	pub fn withdraw_unbonded(runtime: &mut T, origin: T::RuntimeOrigin) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let system: &system::Pallet<T> = runtime.pallet();
+		let now = system.block_number();
+
+		let staking: &mut Self = runtime.pallet_mut();
+		let mut ledger = staking.ledger(&caller);
+		let mut amount = T::Balance::zero();
+		for (value, _) in ledger.unlocking.iter().filter(|(_, unlock_at)| *unlock_at <= now) {
+			amount = amount.checked_add(value).ok_or("Fund overflow.")?;
+		}
+		ledger.unlocking.retain(|(_, unlock_at)| *unlock_at > now);
+
+		Self::update_ledger(runtime, &caller, ledger);
+		Self::deposit_event(runtime, Event::Withdrawn { stash: caller, amount });
+		Ok(())
+	}
+
+	/// Declare the desire of the caller to validate, with its bonded balance.
+	pub fn validate(runtime: &mut T, origin: T::RuntimeOrigin) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let staking: &mut Self = runtime.pallet_mut();
+		staking.ensure_bonded(&caller)?;
+		staking.nominators.remove(&caller);
+		staking.validators.insert(caller);
+		Ok(())
+	}
+
+	/// Declare the desire of the caller to nominate the validators `targets`, with its bonded
+	/// balance.
+	pub fn nominate(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		targets: Vec<T::AccountId>,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let staking: &mut Self = runtime.pallet_mut();
+		staking.ensure_bonded(&caller)?;
+		if targets.is_empty() {
+			return Err("No validator to nominate")
+		}
+		if targets.iter().any(|target| !staking.validators.contains(target)) {
+			return Err("Target is not a validator")
+		}
+		staking.validators.remove(&caller);
+		staking.nominators.insert(caller, targets);
+		Ok(())
+	}
+
+	/// Stop validating or nominating. The balance of the caller stays bonded.
+	pub fn chill(runtime: &mut T, origin: T::RuntimeOrigin) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+
+		let staking: &mut Self = runtime.pallet_mut();
+		staking.validators.remove(&caller);
+		staking.nominators.remove(&caller);
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Staking Pallet.
+	pub fn new() -> Self {
+		Self {
+			ledgers: BTreeMap::new(),
+			validators: BTreeSet::new(),
+			nominators: BTreeMap::new(),
+			current_era: 0,
+			era_end: T::ERA_LENGTH,
+		}
+	}
+
+	/// Get the bonded balance of `who`.
+	pub fn ledger(&self, who: &T::AccountId) -> StakingLedger<T::Balance, T::BlockNumber> {
+		self.ledgers
+			.get(who)
+			.cloned()
+			.unwrap_or(StakingLedger { active: T::Balance::zero(), unlocking: Vec::new() })
+	}
+
+	/// Check whether `who` wants to validate.
+	pub fn is_validator(&self, who: &T::AccountId) -> bool {
+		self.validators.contains(who)
+	}
+
+	/// Get the validators nominated by `who`.
+	pub fn nominations(&self, who: &T::AccountId) -> &[T::AccountId] {
+		self.nominators.get(who).map(Vec::as_slice).unwrap_or(&[])
+	}
+
+	/// Get the index of the current era.
+	pub fn current_era(&self) -> EraIndex {
+		self.current_era
+	}
+
+	/// Check that `who` has some active bonded balance.
+	fn ensure_bonded(&self, who: &T::AccountId) -> DispatchResult {
+		if self.ledger(who).active.is_zero() {
+			return Err("Account is not bonded")
+		}
+		Ok(())
+	}
+
+	/// The stakers who take part in an era with the given `validators`, with their active bonded
+	/// balance: the validators, and the nominators of at least one of them.
+	fn stakers(&self, validators: &BTreeSet<T::AccountId>) -> Vec<(T::AccountId, T::Balance)> {
+		let nominators = self
+			.nominators
+			.iter()
+			.filter(|(_, targets)| targets.iter().any(|target| validators.contains(target)));
+		validators
+			.iter()
+			.chain(nominators.map(|(nominator, _)| nominator))
+			.map(|who| (who.clone(), self.ledger(who).active))
+			.filter(|(_, active)| !active.is_zero())
+			.collect()
+	}
+
+	/// Store the `ledger` of `who`, and lock its total balance.
+	fn update_ledger(
+		runtime: &mut T,
+		who: &T::AccountId,
+		ledger: StakingLedger<T::Balance, T::BlockNumber>,
+	) where
+		T: GetPallet<Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		let total = ledger.total().unwrap_or(ledger.active);
+
+		let staking: &mut Self = runtime.pallet_mut();
+		if total.is_zero() {
+			staking.ledgers.remove(who);
+			staking.validators.remove(who);
+			staking.nominators.remove(who);
+		} else {
+			staking.ledgers.insert(who.clone(), ledger);
+		}
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		if total.is_zero() {
+			balances.remove_lock(STAKING_ID, who);
+		} else {
+			balances.set_lock(STAKING_ID, who, total);
+		}
+	}
+
+	/// Mint the reward of the era, and share it between the stakers in proportion to their
+	/// active bonded balance.
+	fn pay_era(runtime: &mut T)
+	where
+		T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		let staking: &mut Self = runtime.pallet_mut();
+		let era_index = staking.current_era;
+		staking.current_era += 1;
+		let validators = Self::elect_validators(runtime, era_index);
+		let staking: &Self = runtime.pallet();
+		let stakers = staking.stakers(&validators);
+
+		let total_stake = stakers
+			.iter()
+			.try_fold(T::Balance::zero(), |total, (_, active)| total.checked_add(active));
+		if let Some(total_stake) = total_stake.filter(|total| !total.is_zero()) {
+			for (stash, active) in stakers {
+				let amount = T::ERA_REWARD
+					.checked_mul(&active)
+					.and_then(|reward| reward.checked_div(&total_stake))
+					.unwrap_or(T::Balance::zero());
+				let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+				if amount.is_zero() || balances.mint(&stash, amount).is_err() {
+					continue
+				}
+				Self::deposit_event(runtime, Event::Rewarded { stash, amount });
+			}
+		}
+
+		Self::deposit_event(runtime, Event::EraPaid { era_index });
+	}
+
+	/// Get the validators of the era `era_index`: all the stakers who want to validate, or
+	/// `MAX_VALIDATORS` of them drawn at random if there are more.
+	fn elect_validators(runtime: &T, era_index: EraIndex) -> BTreeSet<T::AccountId>
+	where
+		T: GetPallet<Pallet<T>>,
+	{
+		let staking: &Self = runtime.pallet();
+		let max = T::MAX_VALIDATORS as usize;
+		if staking.validators.len() <= max {
+			return staking.validators.clone()
+		}
+
+		let subject = [STAKING_ID.as_slice(), &era_index.to_le_bytes()].concat();
+		let seed = T::Randomness::random(runtime, &subject);
+		let mut candidates = staking.validators.iter().cloned().collect::<Vec<_>>();
+		candidates.sort_by_cached_key(|validator| hash_of(&(seed, validator)));
+		candidates.into_iter().take(max).collect()
+	}
+
+	fn deposit_event(runtime: &mut T, event: Event<T::AccountId, T::Balance>)
+	where
+		T: GetPallet<system::Pallet<T>>,
+	{
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(event);
+	}
+}
+
+impl<T> Hooks<T, T::BlockNumber> for Pallet<T>
+where
+	T: Config
+		+ GetPallet<Pallet<T>>
+		+ GetPallet<system::Pallet<T>>
+		+ GetPallet<balances::Pallet<T>>,
+{
+	/// Pay the rewards of the current era, if it ends with the block `n`.
+	fn on_finalize(runtime: &mut T, n: T::BlockNumber) {
+		let staking: &mut Self = runtime.pallet_mut();
+		if n < staking.era_end {
+			return
+		}
+		staking.era_end = n.checked_add(&T::ERA_LENGTH).unwrap_or(n);
+		Self::pay_era(runtime);
+	}
+}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{support::Origin, Runtime, RuntimeEvent};
+
+	#[test]
+	fn bond_locks_balance() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+
+		// Act
+		let res = Pallet::bond(&mut runtime, Origin::Signed(alice.clone()), 60);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.staking.ledger(&alice).active, 60);
+		assert_eq!(runtime.balances.locked_balance(&alice), 60);
+		assert_eq!(
+			runtime.balances.transfer(Origin::Signed(alice.clone()), bob, 50),
+			Err("Not enough unlocked funds.")
+		);
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::staking(Event::Bonded { stash: alice.clone(), amount: 60 })]
+		);
+		assert_eq!(
+			Pallet::bond(&mut runtime, Origin::Signed(alice), 50),
+			Err("Not enough funds to bond.")
+		);
+	}
+
+	#[test]
+	fn unbond_and_withdraw_after_bonding_duration() {
+		// Arrange
+		let (mut runtime, alice, _) = setup();
+		let _ = Pallet::bond(&mut runtime, Origin::Signed(alice.clone()), 60);
+
+		// Act
+		let res = Pallet::unbond(&mut runtime, Origin::Signed(alice.clone()), 40);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(
+			runtime.staking.ledger(&alice),
+			StakingLedger { active: 20, unlocking: vec![(40, 20)] }
+		);
+		assert_eq!(runtime.balances.locked_balance(&alice), 60);
+
+		advance_to(&mut runtime, 19);
+		assert_eq!(Pallet::withdraw_unbonded(&mut runtime, Origin::Signed(alice.clone())), Ok(()));
+		assert_eq!(runtime.balances.locked_balance(&alice), 60);
+
+		advance_to(&mut runtime, 20);
+		assert_eq!(Pallet::withdraw_unbonded(&mut runtime, Origin::Signed(alice.clone())), Ok(()));
+		assert_eq!(runtime.staking.ledger(&alice), StakingLedger { active: 20, unlocking: vec![] });
+		assert_eq!(runtime.balances.locked_balance(&alice), 20);
+		assert_eq!(
+			Pallet::unbond(&mut runtime, Origin::Signed(alice), 30),
+			Err("Not enough bonded funds.")
+		);
+	}
+
+	#[test]
+	fn withdraw_all_removes_staker() {
+		// Arrange
+		let (mut runtime, alice, _) = setup();
+		let _ = Pallet::bond(&mut runtime, Origin::Signed(alice.clone()), 60);
+		let _ = Pallet::validate(&mut runtime, Origin::Signed(alice.clone()));
+		let _ = Pallet::unbond(&mut runtime, Origin::Signed(alice.clone()), 60);
+		advance_to(&mut runtime, 20);
+
+		// Act
+		let res = Pallet::withdraw_unbonded(&mut runtime, Origin::Signed(alice.clone()));
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.locked_balance(&alice), 0);
+		assert!(!runtime.staking.is_validator(&alice));
+	}
+
+	#[test]
+	fn validate_and_nominate() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::bond(&mut runtime, Origin::Signed(alice.clone()), 60);
+		let _ = Pallet::bond(&mut runtime, Origin::Signed(bob.clone()), 20);
+
+		// Act & Assert
+		assert_eq!(
+			Pallet::nominate(&mut runtime, Origin::Signed(bob.clone()), vec![alice.clone()]),
+			Err("Target is not a validator")
+		);
+		assert_eq!(
+			Pallet::nominate(&mut runtime, Origin::Signed(bob.clone()), vec![]),
+			Err("No validator to nominate")
+		);
+		assert_eq!(
+			Pallet::validate(&mut runtime, Origin::Signed(String::from("charlie"))),
+			Err("Account is not bonded")
+		);
+		assert_eq!(Pallet::validate(&mut runtime, Origin::Signed(alice.clone())), Ok(()));
+		assert_eq!(
+			Pallet::nominate(&mut runtime, Origin::Signed(bob.clone()), vec![alice.clone()]),
+			Ok(())
+		);
+		assert!(runtime.staking.is_validator(&alice));
+		assert_eq!(runtime.staking.nominations(&bob), &[alice]);
+
+		assert_eq!(Pallet::chill(&mut runtime, Origin::Signed(bob.clone())), Ok(()));
+		assert!(runtime.staking.nominations(&bob).is_empty());
+	}
+
+	#[test]
+	fn era_rewards_are_shared_by_stake() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let charlie = String::from("charlie");
+		runtime.balances.set_balance(&charlie, 100);
+		let _ = Pallet::bond(&mut runtime, Origin::Signed(alice.clone()), 60);
+		let _ = Pallet::bond(&mut runtime, Origin::Signed(bob.clone()), 20);
+		let _ = Pallet::bond(&mut runtime, Origin::Signed(charlie.clone()), 50);
+		let _ = Pallet::validate(&mut runtime, Origin::Signed(alice.clone()));
+		let _ = Pallet::nominate(&mut runtime, Origin::Signed(bob.clone()), vec![alice.clone()]);
+		runtime.system.reset_events();
+
+		// Act
+		Pallet::on_finalize(&mut runtime, 9);
+		Pallet::on_finalize(&mut runtime, 10);
+
+		// Assert
+		assert_eq!(runtime.staking.current_era(), 1);
+		assert_eq!(runtime.balances.balance(&alice), 175);
+		assert_eq!(runtime.balances.balance(&bob), 125);
+		assert_eq!(runtime.balances.balance(&charlie), 100);
+		assert_eq!(
+			runtime.system.events(),
+			&[
+				RuntimeEvent::staking(Event::Rewarded { stash: alice, amount: 75 }),
+				RuntimeEvent::staking(Event::Rewarded { stash: bob, amount: 25 }),
+				RuntimeEvent::staking(Event::EraPaid { era_index: 0 }),
+			]
+		);
+	}
+
+	#[test]
+	fn validators_are_drawn_when_above_maximum() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let charlie = String::from("charlie");
+		let dave = String::from("dave");
+		for who in [&alice, &bob, &charlie, &dave] {
+			runtime.balances.set_balance(who, 100);
+			let _ = Pallet::bond(&mut runtime, Origin::Signed(who.clone()), 50);
+			let _ = Pallet::validate(&mut runtime, Origin::Signed(who.clone()));
+		}
+		let elected = Pallet::elect_validators(&runtime, 0);
+		runtime.system.reset_events();
+
+		// Act
+		Pallet::on_finalize(&mut runtime, 10);
+
+		// Assert
+		assert_eq!(elected.len(), 3);
+		let rewarded = runtime
+			.system
+			.events()
+			.iter()
+			.filter_map(|event| match event {
+				RuntimeEvent::staking(Event::Rewarded { stash, amount: 33 }) => Some(stash.clone()),
+				_ => None,
+			})
+			.collect::<BTreeSet<_>>();
+		assert_eq!(rewarded, elected);
+		let all = BTreeSet::from([alice, bob, charlie, dave]);
+		let elections = (0..8).map(|era| Pallet::elect_validators(&runtime, era));
+		assert!(elections.clone().all(|validators| validators.is_subset(&all)));
+		assert!(elections.collect::<BTreeSet<_>>().len() > 1);
+	}
+
+	fn advance_to(runtime: &mut Runtime, block_number: u32) {
+		while runtime.system.block_number() < block_number {
+			runtime.system.inc_block_number();
+		}
+	}
+
+	fn setup() -> (Runtime, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&alice, 100);
+		runtime.balances.set_balance(&bob, 100);
+
+		(runtime, alice, bob)
+	}
+}
@@ -0,0 +1,251 @@
+use crate::{
+	support::{
+		Dispatch, DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, Origin, ProvideInherent,
+		ValidateUnsigned,
+	},
+	system::{self, ensure_signed},
+};
+
+pub trait Config:
+	system::Config<RuntimeEvent: From<Event<<Self as system::Config>::AccountId>>> + Sized
+{
+	/// The type of the calls which the sudo key can dispatch with the root origin.
+	type RuntimeCall;
+}
+
+/// The events deposited by the Sudo Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId> {
+	/// A call was dispatched with the root origin, with the given result.
+	Sudid { sudo_result: DispatchResult },
+	/// The sudo key was changed from `old` to `new`.
+	KeyChanged { old: Option<AccountId>, new: AccountId },
+}
+
+/// This is the Sudo Module.
+/// It holds a single key account, which can dispatch any call of the runtime with the root origin.
+/// This allows an operator to make administrative calls, like setting a balance, with extrinsics.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The account which can make sudo calls, if any.
+	key: Option<T::AccountId>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: Dispatch<Origin = T::RuntimeOrigin, Call = T::RuntimeCall>
+		+ GetPallet<Pallet<T>>
+		+ GetPallet<system::Pallet<T>>,
+{
+	/// Dispatch `call` with the root origin on behalf of the sudo key.
+	/// This function fails if the caller is not the sudo key. Otherwise, the result of the
+	/// dispatched call is recorded in a `Sudid` event.
+	#[allow(clippy::boxed_local)]
+	pub fn sudo(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		let sudo: &Self = runtime.pallet();
+		sudo.ensure_key(&caller)?;
+
+		let sudo_result = runtime.dispatch(T::RuntimeOrigin::from(Origin::Root), *call);
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::Sudid { sudo_result });
+		Ok(())
+	}
+
+	/// Change the sudo key to `new`.
+	/// This function can be called by the root origin, e.g. a passed democracy proposal, or by
+	/// the current sudo key.
+	pub fn set_key(runtime: &mut T, origin: T::RuntimeOrigin, new: T::AccountId) -> DispatchResult {
+		let sudo: &mut Self = runtime.pallet_mut();
+		match origin.into() {
+			Origin::Root => {},
+			origin => sudo.ensure_key(&ensure_signed(origin)?)?,
+		}
+
+		let old = sudo.key.replace(new.clone());
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::KeyChanged { old, new });
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Sudo Pallet, without any sudo key.
+	pub fn new() -> Self {
+		Self { key: None }
+	}
+
+	/// Set the initial sudo `key`, for example at genesis.
+	pub fn initialize_key(&mut self, key: &T::AccountId) {
+		self.key = Some(key.clone());
+	}
+
+	/// Get the current sudo key, if any.
+	pub fn key(&self) -> Option<&T::AccountId> {
+		self.key.as_ref()
+	}
+
+	/// Check that `who` is the sudo key.
+	fn ensure_key(&self, who: &T::AccountId) -> DispatchResult {
+		match &self.key {
+			Some(key) if key == who => Ok(()),
+			_ => Err("Caller is not the sudo key"),
+		}
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{balances, Runtime, RuntimeCall, RuntimeEvent};
+
+	#[test]
+	fn sudo_dispatches_root_call() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let call = force_set_balance(&bob, 100);
+
+		// Act
+		let res = Pallet::sudo(&mut runtime, Origin::Signed(alice), Box::new(call));
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&bob), 100);
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::sudo(Event::Sudid { sudo_result: Ok(()) })]
+		);
+	}
+
+	#[test]
+	fn sudo_records_failed_call() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let call = RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 10 });
+
+		// Act
+		let res = Pallet::sudo(&mut runtime, Origin::Signed(alice), Box::new(call));
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::sudo(Event::Sudid { sudo_result: Err("Origin must be signed") })]
+		);
+	}
+
+	#[test]
+	fn sudo_fails_for_other_account() {
+		// Arrange
+		let (mut runtime, _, bob) = setup();
+		let call = force_set_balance(&bob, 100);
+
+		// Act
+		let res = Pallet::sudo(&mut runtime, Origin::Signed(bob.clone()), Box::new(call));
+
+		// Assert
+		assert_eq!(res, Err("Caller is not the sudo key"));
+		assert_eq!(runtime.balances.balance(&bob), 0);
+		assert!(runtime.system.events().is_empty());
+	}
+
+	#[test]
+	fn set_key_success() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+
+		// Act
+		let res = Pallet::set_key(&mut runtime, Origin::Signed(alice.clone()), bob.clone());
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.sudo.key(), Some(&bob));
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::sudo(Event::KeyChanged { old: Some(alice.clone()), new: bob })]
+		);
+		let call = force_set_balance(&alice, 100);
+		assert_eq!(
+			Pallet::sudo(&mut runtime, Origin::Signed(alice), Box::new(call)),
+			Err("Caller is not the sudo key")
+		);
+	}
+
+	#[test]
+	fn set_key_fails_for_other_account() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+
+		// Act
+		let res = Pallet::set_key(&mut runtime, Origin::Signed(bob.clone()), bob);
+
+		// Assert
+		assert_eq!(res, Err("Caller is not the sudo key"));
+		assert_eq!(runtime.sudo.key(), Some(&alice));
+	}
+
+	#[test]
+	fn set_key_with_root_origin() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+
+		// Act
+		let res = Pallet::set_key(&mut runtime, Origin::Root, bob.clone());
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.sudo.key(), Some(&bob));
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::sudo(Event::KeyChanged { old: Some(alice), new: bob })]
+		);
+	}
+
+	#[test]
+	fn sudo_through_runtime_dispatch() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let call = RuntimeCall::sudo(Call::sudo { call: Box::new(force_set_balance(&bob, 100)) });
+
+		// Act
+		let res = runtime.dispatch(Origin::Signed(alice), call);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&bob), 100);
+	}
+
+	fn force_set_balance(who: &str, amount: u128) -> RuntimeCall {
+		RuntimeCall::balances(balances::Call::force_set_balance { who: who.to_string(), amount })
+	}
+
+	fn setup() -> (Runtime, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let mut runtime = Runtime::new();
+		runtime.sudo.initialize_key(&alice);
+
+		(runtime, alice, bob)
+	}
+}
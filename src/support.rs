@@ -1,4 +1,47 @@
+use std::hash::{Hash as StdHash, Hasher};
+
+/// The type of a hash, used to identify blocks and other data in our state machine.
+/// A real blockchain would use a cryptographic hash function like Blake2 or Keccak. To keep things
+/// simple, we use the 64-bit FNV-1a hash function, which unlike the hasher of the standard library
+/// has a fixed specification, so the hashes do not change between Rust releases.
+pub type Hash = u64;
+
+/// Hash any hashable `value`, returning our [`Hash`] type.
+pub fn hash_of<T: StdHash + ?Sized>(value: &T) -> Hash {
+	let mut hasher = Fnv1aHasher::default();
+	value.hash(&mut hasher);
+	hasher.finish()
+}
+
+/// A [`Hasher`] implementing the 64-bit FNV-1a hash function.
+struct Fnv1aHasher(u64);
+
+impl Fnv1aHasher {
+	const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+	const PRIME: u64 = 0x0000_0100_0000_01b3;
+}
+
+impl Default for Fnv1aHasher {
+	fn default() -> Self {
+		Self(Self::OFFSET_BASIS)
+	}
+}
+
+impl Hasher for Fnv1aHasher {
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 ^= u64::from(*byte);
+			self.0 = self.0.wrapping_mul(Self::PRIME);
+		}
+	}
+
+	fn finish(&self) -> u64 {
+		self.0
+	}
+}
+
 /// The most primitive representation of a Blockchain block.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Block<Header, Extrinsic> {
 	/// The block header contains metadata about the block.
 	pub header: Header,
@@ -6,21 +49,35 @@ pub struct Block<Header, Extrinsic> {
 	pub extrinsics: Vec<Extrinsic>,
 }
 
-/// We are using an extremely simplified header which only contains the current block number.
+impl<Header: StdHash, Extrinsic: StdHash> Block<Header, Extrinsic> {
+	/// The hash of this block. Since our header does not contain an extrinsics root, we hash the
+	/// whole block, so that two blocks with the same header but different extrinsics never share a
+	/// hash.
+	pub fn hash(&self) -> Hash {
+		hash_of(self)
+	}
+}
+
+/// We are using an extremely simplified header which only contains the hash of the parent block
+/// and the current block number.
 /// On a real blockchain, you would expect to also find:
-/// - parent block hash
 /// - state root
 /// - extrinsics root
 /// - etc...
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Header<BlockNumber> {
+	pub parent_hash: Hash,
 	pub block_number: BlockNumber,
 }
 
 /// This is an "extrinsic": literally an external message from outside of the blockchain.
 /// This simplified version of an extrinsic tells us who is making the call, and which call they are
 /// making.
+/// An extrinsic without a `caller` is an inherent: data inserted by the block author, like the
+/// timestamp of the block, which is dispatched with the none origin. See [`ProvideInherent`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Extrinsic<Caller, Call> {
-	pub caller: Caller,
+	pub caller: Option<Caller>,
 	pub call: Call,
 }
 
@@ -28,15 +85,200 @@ pub struct Extrinsic<Caller, Call> {
 /// otherwise return a static error message.
 pub type DispatchResult = Result<(), &'static str>;
 
+/// The origin of a call: on whose behalf, if anyone, the call is made.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Origin<AccountId> {
+	/// The highest privilege level, for administrative calls which no account can make.
+	Root,
+	/// The call is signed by an account.
+	Signed(AccountId),
+	/// The call is not made on behalf of anyone.
+	None,
+}
+
 /// A trait which allows us to dispatch an incoming extrinsic to the appropriate state transition
 /// function call.
 pub trait Dispatch {
-	/// The type used to identify the caller of the function.
-	type Caller;
-	/// The state transition function call the caller is trying to access.
+	/// The type used to identify the origin of the function call.
+	type Origin;
+	/// The state transition function call the origin is trying to access.
 	type Call;
 
-	/// This function takes a `caller` and the `call` they want to make, and returns a `Result`
+	/// This function takes an `origin` and the `call` they want to make, and returns a `Result`
 	/// based on the outcome of that function call.
-	fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResult;
+	fn dispatch(&mut self, origin: Self::Origin, call: Self::Call) -> DispatchResult;
+}
+
+/// The names identifying a call of the runtime: the pallet which exposes it, and its function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CallMetadata {
+	/// The name of the pallet, as declared in the `Runtime` struct.
+	pub pallet_name: &'static str,
+	/// The name of the function of the call.
+	pub function_name: &'static str,
+}
+
+/// Get the name of the function of a call. `#[macros::call]` implements this trait on the `Call`
+/// enum of every pallet.
+pub trait GetCallName {
+	/// The name of the function this call dispatches to.
+	fn function_name(&self) -> &'static str;
+}
+
+/// Get the [`CallMetadata`] of a call, allowing to classify calls by pallet and function.
+/// `#[macros::runtime]` implements this trait on the `RuntimeCall` enum.
+pub trait GetCallMetadata {
+	/// The pallet and function names of this call.
+	fn call_metadata(&self) -> CallMetadata;
+}
+
+/// Access to the instance of the pallet `P` held by the runtime.
+///
+/// `#[macros::runtime]` implements this trait on the runtime for every pallet it includes, so that
+/// code running with access to the whole runtime, like the block hooks, can reach the state of any
+/// pallet. Since the runtime implements it for many pallets, the wanted pallet is usually selected
+/// by the type annotation: `let balances: &mut balances::Pallet<T> = runtime.pallet_mut();`.
+pub trait GetPallet<P> {
+	/// Get a reference to the pallet.
+	fn pallet(&self) -> &P;
+	/// Get a mutable reference to the pallet.
+	fn pallet_mut(&mut self) -> &mut P;
+}
+
+/// Hooks which a pallet can implement to execute logic at the beginning and at the end of every
+/// block. They are called by the runtime for every pallet, in the order the pallets are declared in
+/// the `Runtime` struct.
+///
+/// The hooks are given the whole `Runtime`, so that they can access other pallets as well (see
+/// [`GetPallet`]). Pallets without any per-block logic can rely on the default implementation.
+pub trait Hooks<Runtime, BlockNumber> {
+	/// Called at the beginning of the block `n`, before any extrinsic is dispatched.
+	fn on_initialize(_runtime: &mut Runtime, _n: BlockNumber) {}
+
+	/// Called at the end of the block `n`, after all the extrinsics were dispatched.
+	fn on_finalize(_runtime: &mut Runtime, _n: BlockNumber) {}
+}
+
+/// A trait implemented by every pallet to declare the inherents it owns: calls which the block
+/// author inserts at the start of a block, without a caller or a nonce.
+///
+/// The runtime rejects a block whose inherents do not come before its other extrinsics, which is
+/// missing the inherent of a pallet requiring one, or whose inherents fail. Pallets without
+/// inherents can rely on the default implementation.
+pub trait ProvideInherent<Call> {
+	/// Whether every block must contain an inherent of this pallet.
+	const INHERENT_REQUIRED: bool = false;
+
+	/// Whether `call` is an inherent of this pallet.
+	fn is_inherent(_call: &Call) -> bool {
+		false
+	}
+}
+
+/// The priority of a transaction: transactions with a higher priority are included first.
+pub type TransactionPriority = u64;
+
+/// The number of blocks a transaction stays valid for, once it has been validated.
+pub type TransactionLongevity = u64;
+
+/// The information on a valid unsigned transaction, given by the pallet accepting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValidTransaction {
+	/// The priority of the transaction.
+	pub priority: TransactionPriority,
+	/// The number of blocks the transaction stays valid for.
+	pub longevity: TransactionLongevity,
+}
+
+/// The result of the validation of an unsigned transaction: either the information on the valid
+/// transaction, or the reason why it is invalid.
+pub type TransactionValidity = Result<ValidTransaction, &'static str>;
+
+/// A trait implemented by every pallet to accept or reject its unsigned transactions: calls which
+/// are not an inherent, and are submitted without a caller, e.g. by an account without any funds.
+///
+/// The runtime rejects a block containing an unsigned transaction which its pallet does not
+/// accept. Pallets without unsigned transactions can rely on the default implementation, which
+/// rejects them all.
+pub trait ValidateUnsigned<Runtime, Call> {
+	/// Check whether the unsigned `call` is valid, given the current state of the `runtime`.
+	fn validate_unsigned(_runtime: &Runtime, _call: &Call) -> TransactionValidity {
+		Err("Unsigned transaction is not allowed")
+	}
+}
+
+/// A source of randomness, which pallets needing random values declare in their `Config`, like
+/// `type Randomness: Randomness<Self>` of the Staking Pallet, and which the runtime provides, e.g.
+/// with the Randomness Pallet.
+///
+/// The values are deterministic: every node executing the same blocks gets the same values. They
+/// are therefore predictable, and must not be relied upon where guessing them gives an advantage.
+pub trait Randomness<Runtime> {
+	/// Get a random value for `subject`, which depends on the blocks executed so far. Different
+	/// subjects give unrelated values.
+	fn random(runtime: &Runtime, subject: &[u8]) -> Hash;
+}
+
+/// The version of the layout of the storage of a pallet.
+pub type StorageVersion = u16;
+
+/// A trait implemented by every pallet to migrate the state it holds when the layout of its
+/// storage changes.
+///
+/// The pallet declares the version of the layout its current code expects with `STORAGE_VERSION`,
+/// and remembers the version of the layout of the state it actually holds. The runtime calls
+/// `on_runtime_upgrade` whenever the code version is newer than the stored one. Pallets which never
+/// changed their storage can rely on the default implementation.
+pub trait OnRuntimeUpgrade {
+	/// The version of the storage layout expected by the current code of the pallet.
+	const STORAGE_VERSION: StorageVersion = 0;
+
+	/// The version of the storage layout of the state held by the pallet.
+	fn on_chain_storage_version(&self) -> StorageVersion {
+		0
+	}
+
+	/// Migrate the state held by the pallet to `STORAGE_VERSION`, including the stored version.
+	fn on_runtime_upgrade(&mut self) {}
+
+	/// Check the post-conditions of the migration, after `on_runtime_upgrade` was executed.
+	fn post_upgrade(&self) -> DispatchResult {
+		Ok(())
+	}
+}
+
+/// A test harness for storage migrations.
+/// It runs the migration of `pallet`, usually a fixture snapshot of the state at an older storage
+/// version, and checks that the stored version was updated and that the post-conditions hold.
+pub fn try_runtime_upgrade<P: OnRuntimeUpgrade>(pallet: &mut P) -> DispatchResult {
+	if pallet.on_chain_storage_version() >= P::STORAGE_VERSION {
+		return Err("Storage is already up to date")
+	}
+
+	pallet.on_runtime_upgrade();
+
+	if pallet.on_chain_storage_version() != P::STORAGE_VERSION {
+		return Err("Migration did not update the storage version")
+	}
+	pallet.post_upgrade()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fnv1a_hasher_matches_specification() {
+		// Arrange
+		let hash = |bytes: &[u8]| {
+			let mut hasher = Fnv1aHasher::default();
+			hasher.write(bytes);
+			hasher.finish()
+		};
+
+		// Act & Assert
+		assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+		assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+		assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+	}
 }
@@ -2,26 +2,44 @@ use std::{collections::BTreeMap, ops::AddAssign};
 
 use num::{One, Zero};
 
+use crate::support::{DispatchResult, GetPallet, Hash, Hooks, OnRuntimeUpgrade, Origin};
+
 pub trait Config {
 	type AccountId: Ord + Clone;
+	/// The origin of the calls dispatched by the runtime. See [`Origin`].
+	type RuntimeOrigin: Clone + From<Origin<Self::AccountId>> + Into<Origin<Self::AccountId>>;
+	/// The events deposited by the pallets of the runtime.
+	type RuntimeEvent;
 	type BlockNumber: Zero + One + Copy + AddAssign;
 	type Nonce: Clone + Copy + Zero + One;
 }
 
 /// This is the System Pallet
 /// It handles low level state needed for your blockchain.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
 	/// The current block number
 	block_number: T::BlockNumber,
+	/// The number of the last finalized block
+	finalized_number: T::BlockNumber,
+	/// The hash of the parent of the current block
+	parent_hash: Hash,
 	/// A map from an account to their nonce
 	nonce: BTreeMap<T::AccountId, T::Nonce>,
+	/// The events deposited during the current block
+	events: Vec<T::RuntimeEvent>,
 }
 
 impl<T: Config> Pallet<T> {
 	/// Create a new instance of the System Pallet
 	pub fn new() -> Self {
-		Self { block_number: T::BlockNumber::zero(), nonce: BTreeMap::new() }
+		Self {
+			block_number: T::BlockNumber::zero(),
+			finalized_number: T::BlockNumber::zero(),
+			parent_hash: 0,
+			nonce: BTreeMap::new(),
+			events: Vec::new(),
+		}
 	}
 
 	/// Get the current block number.
@@ -29,6 +47,28 @@ impl<T: Config> Pallet<T> {
 		self.block_number
 	}
 
+	/// Get the number of the last finalized block.
+	pub fn finalized_number(&self) -> T::BlockNumber {
+		self.finalized_number
+	}
+
+	/// Set the number of the last finalized block. Blocks up to this number can no longer be
+	/// reverted.
+	pub fn set_finalized_number(&mut self, number: T::BlockNumber) {
+		self.finalized_number = number;
+	}
+
+	/// Get the hash of the parent of the current block.
+	pub fn parent_hash(&self) -> Hash {
+		self.parent_hash
+	}
+
+	/// Set the hash of the parent of the current block, from the header of the block being
+	/// executed.
+	pub fn set_parent_hash(&mut self, parent_hash: Hash) {
+		self.parent_hash = parent_hash;
+	}
+
 	pub fn get_nonce(&self, who: &T::AccountId) -> T::Nonce {
 		*self.nonce.get(who).unwrap_or(&T::Nonce::zero())
 	}
@@ -45,6 +85,61 @@ impl<T: Config> Pallet<T> {
 		let nonce = *self.nonce.get(who).unwrap_or(&T::Nonce::zero());
 		self.nonce.insert(who.clone(), nonce + T::Nonce::one());
 	}
+
+	/// Get the events deposited during the current block.
+	pub fn events(&self) -> &[T::RuntimeEvent] {
+		&self.events
+	}
+
+	/// Deposit an event, recording that something happened during the current block.
+	pub fn deposit_event(&mut self, event: impl Into<T::RuntimeEvent>) {
+		self.events.push(event.into());
+	}
+
+	/// Clear the events of the previous block.
+	pub fn reset_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+/// Ensure that the `origin` is signed by an account, and return that account.
+pub fn ensure_signed<AccountId>(
+	origin: impl Into<Origin<AccountId>>,
+) -> Result<AccountId, &'static str> {
+	match origin.into() {
+		Origin::Signed(who) => Ok(who),
+		_ => Err("Origin must be signed"),
+	}
+}
+
+/// Ensure that the `origin` is root.
+pub fn ensure_root<AccountId>(origin: impl Into<Origin<AccountId>>) -> DispatchResult {
+	match origin.into() {
+		Origin::Root => Ok(()),
+		_ => Err("Origin must be root"),
+	}
+}
+
+/// Ensure that the `origin` is none, i.e. the call is not made on behalf of anyone.
+pub fn ensure_none<AccountId>(origin: impl Into<Origin<AccountId>>) -> DispatchResult {
+	match origin.into() {
+		Origin::None => Ok(()),
+		_ => Err("Origin must be none"),
+	}
+}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config + GetPallet<Pallet<T>>> Hooks<T, T::BlockNumber> for Pallet<T> {
+	fn on_initialize(runtime: &mut T, _n: T::BlockNumber) {
+		runtime.pallet_mut().reset_events();
+	}
+}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 #[cfg(test)]
@@ -55,6 +150,8 @@ mod tests {
 
 	impl Config for TestConfig {
 		type AccountId = String;
+		type RuntimeOrigin = Origin<String>;
+		type RuntimeEvent = &'static str;
 		type BlockNumber = u32;
 		type Nonce = u32;
 	}
@@ -66,6 +163,8 @@ mod tests {
 		let system = Pallet::<TestConfig>::new();
 		// Assert
 		assert_eq!(system.block_number(), 0);
+		assert_eq!(system.finalized_number(), 0);
+		assert_eq!(system.parent_hash(), 0);
 	}
 
 	#[test]
@@ -78,6 +177,58 @@ mod tests {
 		assert_eq!(system.block_number(), 1);
 	}
 
+	#[test]
+	fn set_finalized_number() {
+		// Arrange
+		let mut system = Pallet::<TestConfig>::new();
+		system.inc_block_number();
+		system.inc_block_number();
+		// Act
+		system.set_finalized_number(1);
+		// Assert
+		assert_eq!(system.finalized_number(), 1);
+		assert_eq!(system.block_number(), 2);
+	}
+
+	#[test]
+	fn set_parent_hash() {
+		// Arrange
+		let mut system = Pallet::<TestConfig>::new();
+		// Act
+		system.set_parent_hash(42);
+		// Assert
+		assert_eq!(system.parent_hash(), 42);
+	}
+
+	#[test]
+	fn deposit_event() {
+		// Arrange
+		let mut system = Pallet::<TestConfig>::new();
+		// Act
+		system.deposit_event("first");
+		system.deposit_event("second");
+		// Assert
+		assert_eq!(system.events(), &["first", "second"]);
+		system.reset_events();
+		assert!(system.events().is_empty());
+	}
+
+	#[test]
+	fn ensure_origin() {
+		// Arrange
+		let alice = <TestConfig as Config>::AccountId::from("alice");
+		let signed = <TestConfig as Config>::RuntimeOrigin::Signed(alice.clone());
+		let root = <TestConfig as Config>::RuntimeOrigin::Root;
+		let none = <TestConfig as Config>::RuntimeOrigin::None;
+		// Act & Assert
+		assert_eq!(ensure_signed(signed.clone()), Ok(alice));
+		assert_eq!(ensure_signed(root.clone()), Err("Origin must be signed"));
+		assert_eq!(ensure_root(root.clone()), Ok(()));
+		assert_eq!(ensure_root(none.clone()), Err("Origin must be root"));
+		assert_eq!(ensure_none(none), Ok(()));
+		assert_eq!(ensure_none(signed), Err("Origin must be none"));
+	}
+
 	#[test]
 	fn inc_nonce() {
 		// Arrange
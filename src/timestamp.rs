@@ -0,0 +1,234 @@
+use num::{CheckedAdd, Zero};
+
+use crate::{
+	support::{
+		DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, ProvideInherent, ValidateUnsigned,
+	},
+	system::{ensure_none, Config as SystemConfig},
+};
+
+pub trait Config: SystemConfig {
+	/// The type of a point in time, e.g. milliseconds since the Unix epoch.
+	type Moment: CheckedAdd + Zero + Copy + Ord;
+	/// The minimum time between the timestamps of two consecutive blocks.
+	const MINIMUM_PERIOD: Self::Moment;
+}
+
+/// This is the Timestamp Module.
+/// It keeps track of the wall-clock time of the current block, which the block author sets once
+/// per block with the `set` call, and which other pallets can read with `now`.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The timestamp of the current block.
+	now: T::Moment,
+	/// Whether the timestamp was already set in the current block.
+	did_update: bool,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Set the timestamp of the current block to `now`.
+	/// This call is made by the block author, with the none origin, exactly once per block. The
+	/// timestamp must be at least `MINIMUM_PERIOD` after the timestamp of the previous block, or
+	/// after zero for the first block.
+	pub fn set(&mut self, origin: T::RuntimeOrigin, now: T::Moment) -> DispatchResult {
+		ensure_none(origin)?;
+		if self.did_update {
+			return Err("Timestamp must be updated only once in the block")
+		}
+		let earliest = self.now.checked_add(&T::MINIMUM_PERIOD).ok_or("Timestamp overflow")?;
+		if now < earliest {
+			return Err("Timestamp must increment by at least the minimum period")
+		}
+
+		self.now = now;
+		self.did_update = true;
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Timestamp Pallet.
+	pub fn new() -> Self {
+		Self { now: T::Moment::zero(), did_update: false }
+	}
+
+	/// Get the timestamp of the current block, or zero before it is first set.
+	pub fn now(&self) -> T::Moment {
+		self.now
+	}
+
+	/// Check whether the timestamp was set in the current block.
+	pub fn did_update(&self) -> bool {
+		self.did_update
+	}
+}
+
+impl<T: Config + GetPallet<Pallet<T>>> Hooks<T, T::BlockNumber> for Pallet<T> {
+	/// Allow the timestamp to be set again in the next block.
+	fn on_finalize(runtime: &mut T, _n: T::BlockNumber) {
+		let timestamp: &mut Self = runtime.pallet_mut();
+		timestamp.did_update = false;
+	}
+}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+/// The `set` call is the inherent of the Timestamp Pallet, which every block must contain.
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {
+	const INHERENT_REQUIRED: bool = true;
+
+	fn is_inherent(call: &Call<T>) -> bool {
+		matches!(call, Call::set { .. })
+	}
+}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		balances,
+		support::{Extrinsic, Header, Origin},
+		types, RuntimeCall,
+	};
+
+	struct TestConfig;
+
+	impl SystemConfig for TestConfig {
+		type AccountId = String;
+		type RuntimeOrigin = Origin<String>;
+		type RuntimeEvent = ();
+		type BlockNumber = u32;
+		type Nonce = u32;
+	}
+
+	impl Config for TestConfig {
+		type Moment = u64;
+		const MINIMUM_PERIOD: u64 = 1_000;
+	}
+
+	#[test]
+	fn set_timestamp() {
+		// Arrange
+		let mut timestamp = Pallet::<TestConfig>::new();
+		// Act
+		let result = timestamp.set(Origin::None, 42_000);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(timestamp.now(), 42_000);
+		assert!(timestamp.did_update());
+	}
+
+	#[test]
+	fn set_timestamp_errors() {
+		// Arrange
+		let mut timestamp = Pallet::<TestConfig>::new();
+		let _ = timestamp.set(Origin::None, 42_000);
+		// Act & Assert
+		assert_eq!(
+			timestamp.set(Origin::Signed(String::from("alice")), 50_000),
+			Err("Origin must be none")
+		);
+		assert_eq!(
+			timestamp.set(Origin::None, 50_000),
+			Err("Timestamp must be updated only once in the block")
+		);
+		timestamp.did_update = false;
+		assert_eq!(
+			timestamp.set(Origin::None, 42_500),
+			Err("Timestamp must increment by at least the minimum period")
+		);
+		assert_eq!(timestamp.set(Origin::None, 43_000), Ok(()));
+	}
+
+	#[test]
+	fn set_timestamp_zero_is_rejected() {
+		// Arrange
+		let mut timestamp = Pallet::<TestConfig>::new();
+		// Act
+		let first = timestamp.set(Origin::None, 0);
+		let second = timestamp.set(Origin::None, 0);
+		// Assert
+		assert_eq!(first, Err("Timestamp must increment by at least the minimum period"));
+		assert_eq!(second, Err("Timestamp must increment by at least the minimum period"));
+		assert!(!timestamp.did_update());
+		assert_eq!(timestamp.set(Origin::None, 999), second);
+		assert_eq!(timestamp.set(Origin::None, 1_000), Ok(()));
+	}
+
+	#[test]
+	fn on_finalize_allows_next_update() {
+		// Arrange
+		let mut runtime = crate::Runtime::new();
+		let _ = runtime.timestamp.set(Origin::None, 42_000);
+		// Act
+		Pallet::on_finalize(&mut runtime, 1);
+		// Assert
+		assert!(!runtime.timestamp.did_update());
+		assert_eq!(runtime.timestamp.now(), 42_000);
+		assert_eq!(runtime.timestamp.set(Origin::None, 43_000), Ok(()));
+	}
+
+	#[test]
+	fn execute_block_with_timestamp_inherent() {
+		// Arrange
+		let mut runtime = crate::Runtime::new();
+		let block = block(vec![inherent(1_000), transfer()]);
+		// Act
+		let result = runtime.execute_block(block);
+		// Assert
+		assert_eq!(result, Ok(()));
+		assert_eq!(runtime.timestamp.now(), 1_000);
+		assert_eq!(runtime.system.get_nonce(&String::from("alice")), 1);
+	}
+
+	#[test]
+	fn execute_block_inherent_errors() {
+		// Arrange
+		let runtime = crate::Runtime::new();
+		let unsigned_transfer = Extrinsic { caller: None, ..transfer() };
+		let signed_timestamp = Extrinsic { caller: Some(String::from("alice")), ..inherent(1_000) };
+		// Act & Assert
+		assert_eq!(
+			runtime.clone().execute_block(block(vec![transfer()])),
+			Err("block is missing the inherent of timestamp")
+		);
+		assert_eq!(
+			runtime.clone().execute_block(block(vec![signed_timestamp])),
+			Err("block is missing the inherent of timestamp")
+		);
+		assert_eq!(
+			runtime.clone().execute_block(block(vec![transfer(), inherent(1_000)])),
+			Err("inherents must come before the other extrinsics")
+		);
+		assert_eq!(
+			runtime.clone().execute_block(block(vec![inherent(1_000), unsigned_transfer])),
+			Err("Unsigned transaction is not allowed")
+		);
+		assert_eq!(
+			runtime.clone().execute_block(block(vec![inherent(1_000), inherent(2_000)])),
+			Err("Timestamp must be updated only once in the block")
+		);
+	}
+
+	fn inherent(now: u64) -> types::Extrinsic {
+		Extrinsic { caller: None, call: RuntimeCall::timestamp(Call::set { now }) }
+	}
+
+	fn transfer() -> types::Extrinsic {
+		let call = balances::Call::transfer { to: String::from("bob"), amount: 0 };
+		Extrinsic { caller: Some(String::from("alice")), call: RuntimeCall::balances(call) }
+	}
+
+	fn block(extrinsics: Vec<types::Extrinsic>) -> types::Block {
+		types::Block { header: Header { parent_hash: 0, block_number: 1 }, extrinsics }
+	}
+}
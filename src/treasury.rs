@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+
+use num::{CheckedAdd, Zero};
+
+use crate::{
+	balances,
+	support::{
+		DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, ProvideInherent, ValidateUnsigned,
+	},
+	system::{self, ensure_root, ensure_signed},
+};
+
+/// The index of a spend proposal.
+pub type ProposalIndex = u32;
+
+/// The identifier of the Treasury Pallet, from which the account of the treasury is derived.
+/// The account is `"modl:py/trsry"`: the `modl:` prefix is reserved for the accounts owned by
+/// pallets, so no ordinary account can be the treasury.
+pub const PALLET_ID: &str = "py/trsry";
+
+pub trait Config:
+	balances::Config
+	+ system::Config<
+		AccountId: From<String>,
+		BlockNumber: CheckedAdd + Ord,
+		RuntimeEvent: From<
+			Event<<Self as system::Config>::AccountId, <Self as balances::Config>::Balance>,
+		>,
+	> + Sized
+{
+	/// The number of blocks between two payouts of the approved proposals.
+	const SPEND_PERIOD: Self::BlockNumber;
+	/// The balance reserved from the proposer of a spend, which is slashed if it is rejected.
+	const PROPOSAL_BOND: Self::Balance;
+}
+
+/// A proposal to spend funds of the treasury.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal<AccountId, Balance> {
+	/// The account which submitted the proposal, and reserved the bond.
+	pub proposer: AccountId,
+	/// The balance to pay.
+	pub value: Balance,
+	/// The account to pay.
+	pub beneficiary: AccountId,
+	/// The balance reserved from the proposer.
+	pub bond: Balance,
+}
+
+/// The events deposited by the Treasury Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId, Balance> {
+	/// The treasury received `value`, from fees or slashed funds.
+	Deposit { value: Balance },
+	/// The spend proposal `index` was submitted.
+	Proposed { index: ProposalIndex },
+	/// The spend proposal `index` was approved, and will be paid at the next payout.
+	Approved { index: ProposalIndex },
+	/// The spend proposal `index` was rejected, and the bond of its proposer slashed.
+	Rejected { index: ProposalIndex, slashed: Balance },
+	/// `award` was paid to `beneficiary` for the spend proposal `index`.
+	Awarded { index: ProposalIndex, award: Balance, beneficiary: AccountId },
+}
+
+/// This is the Treasury Module.
+/// It owns an account, which receives slashed funds, and the transaction fees once the runtime
+/// charges them. Anyone can propose to spend some of these funds, by reserving a bond. The root
+/// origin, e.g. a passed democracy proposal, approves or rejects the proposals, and the approved
+/// proposals are paid every `SPEND_PERIOD` blocks, as long as the treasury has enough funds.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The spend proposals which were not paid or rejected yet.
+	proposals: BTreeMap<ProposalIndex, Proposal<T::AccountId, T::Balance>>,
+	/// The approved proposals, in the order they will be paid.
+	approvals: Vec<ProposalIndex>,
+	/// The index of the next spend proposal.
+	next_index: ProposalIndex,
+	/// The block at which the next payout happens.
+	next_payout: T::BlockNumber,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+{
+	/// Propose to pay `value` from the treasury to `beneficiary`. `PROPOSAL_BOND` is reserved
+	/// from the caller until the proposal is paid or rejected.
+	pub fn propose_spend(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		value: T::Balance,
+		beneficiary: T::AccountId,
+	) -> DispatchResult {
+		let proposer = ensure_signed(origin)?;
+
+		let treasury: &Self = runtime.pallet();
+		let index = treasury.next_index;
+		let next_index = index.checked_add(1).ok_or("Proposal index overflow")?;
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.reserve(&proposer, T::PROPOSAL_BOND)?;
+
+		let treasury: &mut Self = runtime.pallet_mut();
+		treasury.next_index = next_index;
+		let proposal = Proposal { proposer, value, beneficiary, bond: T::PROPOSAL_BOND };
+		treasury.proposals.insert(index, proposal);
+
+		Self::deposit_event(runtime, Event::Proposed { index });
+		Ok(())
+	}
+
+	/// Approve the spend proposal `index`, to be paid at the next payout.
+	/// This function can only be called by the root origin.
+	pub fn approve_proposal(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		index: ProposalIndex,
+	) -> DispatchResult {
+		ensure_root(origin)?;
+
+		let treasury: &mut Self = runtime.pallet_mut();
+		if !treasury.proposals.contains_key(&index) {
+			return Err("Proposal not found")
+		}
+		if treasury.approvals.contains(&index) {
+			return Err("Proposal already approved")
+		}
+		treasury.approvals.push(index);
+
+		Self::deposit_event(runtime, Event::Approved { index });
+		Ok(())
+	}
+
+	/// Reject the spend proposal `index`, slashing the bond of its proposer into the treasury.
+	/// This function can only be called by the root origin.
+	pub fn reject_proposal(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		index: ProposalIndex,
+	) -> DispatchResult {
+		ensure_root(origin)?;
+
+		let treasury: &mut Self = runtime.pallet_mut();
+		if treasury.approvals.contains(&index) {
+			return Err("Proposal already approved")
+		}
+		let proposal = treasury.proposals.remove(&index).ok_or("Proposal not found")?;
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.unreserve(&proposal.proposer, proposal.bond)?;
+		let slashed = Self::slash(runtime, &proposal.proposer, proposal.bond);
+
+		Self::deposit_event(runtime, Event::Rejected { index, slashed });
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Treasury Pallet.
+	pub fn new() -> Self {
+		Self {
+			proposals: BTreeMap::new(),
+			approvals: Vec::new(),
+			next_index: 0,
+			next_payout: T::SPEND_PERIOD,
+		}
+	}
+
+	/// Get the account owned by the treasury, derived from `PALLET_ID`.
+	pub fn account_id() -> T::AccountId {
+		format!("modl:{PALLET_ID}").into()
+	}
+
+	/// Get the spend proposal `index`, if it was not paid or rejected yet.
+	pub fn proposal(&self, index: ProposalIndex) -> Option<&Proposal<T::AccountId, T::Balance>> {
+		self.proposals.get(&index)
+	}
+
+	/// Get the approved proposals, in the order they will be paid.
+	pub fn approvals(&self) -> &[ProposalIndex] {
+		&self.approvals
+	}
+
+	/// Transfer the transaction fee `amount` from `who` to the treasury.
+	/// The runtime does not charge transaction fees yet, so this is only the entry point for the
+	/// fees of the signed extrinsics, to be called once they are charged.
+	pub fn pay_fee(runtime: &mut T, who: &T::AccountId, amount: T::Balance) -> DispatchResult
+	where
+		T: GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.do_transfer(who, &Self::account_id(), amount)?;
+
+		Self::deposit_event(runtime, Event::Deposit { value: amount });
+		Ok(())
+	}
+
+	/// Slash up to `amount` from the free balance of `who` into the treasury, regardless of its
+	/// locks. Returns the amount which was actually slashed.
+	/// The slashed funds are burnt if the balance of the treasury would overflow.
+	pub fn slash(runtime: &mut T, who: &T::AccountId, amount: T::Balance) -> T::Balance
+	where
+		T: GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		let slashed = balances.slash(who, amount);
+		let deposited = balances.mint(&Self::account_id(), slashed).is_ok();
+
+		if deposited && !slashed.is_zero() {
+			Self::deposit_event(runtime, Event::Deposit { value: slashed });
+		}
+		slashed
+	}
+
+	/// Pay the approved proposals, in order, as long as the treasury has enough funds. The
+	/// proposals which can not be paid stay approved for the next payout.
+	fn spend_funds(runtime: &mut T)
+	where
+		T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		let treasury: &mut Self = runtime.pallet_mut();
+		let approvals = std::mem::take(&mut treasury.approvals);
+		for index in approvals {
+			let treasury: &mut Self = runtime.pallet_mut();
+			let Some(proposal) = treasury.proposals.get(&index).cloned() else { continue };
+
+			let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+			let paid =
+				balances.do_transfer(&Self::account_id(), &proposal.beneficiary, proposal.value);
+			if paid.is_err() {
+				let treasury: &mut Self = runtime.pallet_mut();
+				treasury.approvals.push(index);
+				continue
+			}
+			let _ = balances.unreserve(&proposal.proposer, proposal.bond);
+
+			let treasury: &mut Self = runtime.pallet_mut();
+			treasury.proposals.remove(&index);
+			let Proposal { value: award, beneficiary, .. } = proposal;
+			Self::deposit_event(runtime, Event::Awarded { index, award, beneficiary });
+		}
+	}
+
+	fn deposit_event(runtime: &mut T, event: Event<T::AccountId, T::Balance>)
+	where
+		T: GetPallet<system::Pallet<T>>,
+	{
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(event);
+	}
+}
+
+impl<T> Hooks<T, T::BlockNumber> for Pallet<T>
+where
+	T: Config
+		+ GetPallet<Pallet<T>>
+		+ GetPallet<system::Pallet<T>>
+		+ GetPallet<balances::Pallet<T>>,
+{
+	/// Pay the approved proposals, if the block `n` ends a spend period.
+	fn on_finalize(runtime: &mut T, n: T::BlockNumber) {
+		let treasury: &mut Self = runtime.pallet_mut();
+		if n < treasury.next_payout {
+			return
+		}
+		treasury.next_payout = n.checked_add(&T::SPEND_PERIOD).unwrap_or(n);
+		Self::spend_funds(runtime);
+	}
+}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{support::Origin, Runtime, RuntimeEvent};
+
+	#[test]
+	fn account_id_is_derived_from_pallet_id() {
+		// Act
+		let treasury = Pallet::<Runtime>::account_id();
+
+		// Assert
+		assert_eq!(treasury, "modl:py/trsry");
+		assert_ne!(treasury, "treasury");
+	}
+
+	#[test]
+	fn pay_fee_and_slash_fund_treasury() {
+		// Arrange
+		let (mut runtime, alice, _) = setup();
+		let treasury = Pallet::<Runtime>::account_id();
+
+		// Act
+		let res = Pallet::pay_fee(&mut runtime, &alice, 10);
+		let slashed = Pallet::slash(&mut runtime, &alice, 200);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(slashed, 90);
+		assert_eq!(runtime.balances.balance(&alice), 0);
+		assert_eq!(runtime.balances.balance(&treasury), 100);
+		assert_eq!(
+			runtime.system.events(),
+			&[
+				RuntimeEvent::treasury(Event::Deposit { value: 10 }),
+				RuntimeEvent::treasury(Event::Deposit { value: 90 }),
+			]
+		);
+		assert_eq!(Pallet::pay_fee(&mut runtime, &alice, 10), Err("Not enough funds."));
+	}
+
+	#[test]
+	fn approved_proposal_is_paid_at_payout() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let treasury = Pallet::<Runtime>::account_id();
+		runtime.balances.set_balance(&treasury, 100);
+		let _ = Pallet::propose_spend(&mut runtime, Origin::Signed(alice.clone()), 40, bob.clone());
+
+		// Act
+		let res = Pallet::approve_proposal(&mut runtime, Origin::Root, 0);
+		Pallet::on_finalize(&mut runtime, 9);
+		let bob_at_block_9 = runtime.balances.balance(&bob);
+		Pallet::on_finalize(&mut runtime, 10);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(bob_at_block_9, 0);
+		assert_eq!(runtime.balances.balance(&bob), 40);
+		assert_eq!(runtime.balances.balance(&treasury), 60);
+		assert_eq!(runtime.balances.account(&alice).reserved, 0);
+		assert_eq!(runtime.treasury.proposal(0), None);
+		assert!(runtime.treasury.approvals().is_empty());
+		assert_eq!(
+			runtime.system.events().last(),
+			Some(&RuntimeEvent::treasury(Event::Awarded { index: 0, award: 40, beneficiary: bob }))
+		);
+	}
+
+	#[test]
+	fn approved_proposal_waits_for_funds() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let treasury = Pallet::<Runtime>::account_id();
+		let _ = Pallet::propose_spend(&mut runtime, Origin::Signed(alice.clone()), 40, bob.clone());
+		let _ = Pallet::approve_proposal(&mut runtime, Origin::Root, 0);
+
+		// Act
+		Pallet::on_finalize(&mut runtime, 10);
+		let approvals_at_block_10 = runtime.treasury.approvals().to_vec();
+		runtime.balances.set_balance(&treasury, 40);
+		Pallet::on_finalize(&mut runtime, 20);
+
+		// Assert
+		assert_eq!(approvals_at_block_10, vec![0]);
+		assert_eq!(runtime.balances.balance(&bob), 40);
+		assert!(runtime.treasury.approvals().is_empty());
+	}
+
+	#[test]
+	fn rejected_proposal_slashes_bond() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::propose_spend(&mut runtime, Origin::Signed(alice.clone()), 40, bob);
+
+		// Act
+		let res = Pallet::reject_proposal(&mut runtime, Origin::Root, 0);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.account(&alice).free, 95);
+		assert_eq!(runtime.balances.account(&alice).reserved, 0);
+		assert_eq!(runtime.balances.balance(&Pallet::<Runtime>::account_id()), 5);
+		assert_eq!(runtime.treasury.proposal(0), None);
+		assert_eq!(
+			runtime.system.events().last(),
+			Some(&RuntimeEvent::treasury(Event::Rejected { index: 0, slashed: 5 }))
+		);
+	}
+
+	#[test]
+	fn proposal_index_overflow_does_not_reserve_bond() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		runtime.treasury.next_index = ProposalIndex::MAX;
+
+		// Act
+		let res = Pallet::propose_spend(&mut runtime, Origin::Signed(alice.clone()), 40, bob);
+
+		// Assert
+		assert_eq!(res, Err("Proposal index overflow"));
+		assert_eq!(runtime.balances.account(&alice).reserved, 0);
+		assert_eq!(runtime.treasury.proposal(ProposalIndex::MAX), None);
+	}
+
+	#[test]
+	fn proposal_errors() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::propose_spend(&mut runtime, Origin::Signed(alice.clone()), 40, bob.clone());
+		let _ = Pallet::approve_proposal(&mut runtime, Origin::Root, 0);
+
+		// Act & Assert
+		assert_eq!(
+			Pallet::propose_spend(&mut runtime, Origin::Signed(bob.clone()), 40, alice.clone()),
+			Err("Not enough funds.")
+		);
+		assert_eq!(
+			Pallet::approve_proposal(&mut runtime, Origin::Signed(alice), 0),
+			Err("Origin must be root")
+		);
+		assert_eq!(
+			Pallet::approve_proposal(&mut runtime, Origin::Root, 0),
+			Err("Proposal already approved")
+		);
+		assert_eq!(
+			Pallet::reject_proposal(&mut runtime, Origin::Root, 0),
+			Err("Proposal already approved")
+		);
+		assert_eq!(
+			Pallet::reject_proposal(&mut runtime, Origin::Root, 1),
+			Err("Proposal not found")
+		);
+	}
+
+	fn setup() -> (Runtime, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&alice, 100);
+
+		(runtime, alice, bob)
+	}
+}
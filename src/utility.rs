@@ -0,0 +1,217 @@
+use crate::{
+	support::{
+		Dispatch, DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, ProvideInherent,
+		ValidateUnsigned,
+	},
+	system,
+};
+
+pub trait Config: system::Config<RuntimeEvent: From<Event>> + Sized {
+	/// The type of the calls which can be batched together.
+	type RuntimeCall;
+}
+
+/// The events deposited by the Utility Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+	/// A call of a batch failed, so the remaining calls were not dispatched.
+	BatchInterrupted { index: u32, error: &'static str },
+	/// All the calls of a batch were dispatched successfully.
+	BatchCompleted,
+}
+
+/// This is the Utility Module.
+/// It allows a user to dispatch many calls with a single extrinsic, instead of one extrinsic per
+/// call. It does not hold any state of its own.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	_config: std::marker::PhantomData<T>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: Dispatch<Origin = T::RuntimeOrigin, Call = T::RuntimeCall>
+		+ GetPallet<Pallet<T>>
+		+ GetPallet<system::Pallet<T>>
+		+ Clone,
+{
+	/// Dispatch `calls` one after the other, with the origin of the batch.
+	/// The batch stops at the first call which fails, which is recorded in a `BatchInterrupted`
+	/// event. The calls dispatched before it are not reverted, and the batch itself succeeds.
+	pub fn batch(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		calls: Vec<T::RuntimeCall>,
+	) -> DispatchResult {
+		for (index, call) in calls.into_iter().enumerate() {
+			if let Err(error) = runtime.dispatch(origin.clone(), call) {
+				let system: &mut system::Pallet<T> = runtime.pallet_mut();
+				system.deposit_event(Event::BatchInterrupted { index: index as u32, error });
+				return Ok(())
+			}
+		}
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::BatchCompleted);
+		Ok(())
+	}
+
+	/// Dispatch `calls` one after the other, with the origin of the batch, atomically.
+	/// If any of the calls fails, the changes made by all the calls are reverted and the batch
+	/// fails with the error of that call.
+	pub fn batch_all(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		calls: Vec<T::RuntimeCall>,
+	) -> DispatchResult {
+		// Our storage is held in memory, so reverting the batch is restoring a copy of the runtime.
+		let snapshot = runtime.clone();
+		for call in calls {
+			if let Err(error) = runtime.dispatch(origin.clone(), call) {
+				*runtime = snapshot;
+				return Err(error)
+			}
+		}
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(Event::BatchCompleted);
+		Ok(())
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Utility Pallet.
+	pub fn new() -> Self {
+		Self { _config: std::marker::PhantomData }
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{balances, support::Origin, Runtime, RuntimeCall, RuntimeEvent};
+
+	#[test]
+	fn batch_success() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let calls = vec![transfer(&bob, 30), transfer(&charlie, 20)];
+
+		// Act
+		let res = Pallet::batch(&mut runtime, Origin::Signed(alice.clone()), calls);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 50);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+		assert_eq!(runtime.balances.balance(&charlie), 20);
+		assert_eq!(runtime.system.events(), &[RuntimeEvent::utility(Event::BatchCompleted)]);
+	}
+
+	#[test]
+	fn batch_stops_at_first_error() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let calls = vec![transfer(&bob, 30), transfer(&charlie, 200), transfer(&charlie, 20)];
+
+		// Act
+		let res = Pallet::batch(&mut runtime, Origin::Signed(alice.clone()), calls);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 70);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+		assert_eq!(runtime.balances.balance(&charlie), 0);
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::utility(Event::BatchInterrupted {
+				index: 1,
+				error: "Not enough funds."
+			})]
+		);
+	}
+
+	#[test]
+	fn batch_all_success() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let calls = vec![transfer(&bob, 30), transfer(&charlie, 20)];
+
+		// Act
+		let res = Pallet::batch_all(&mut runtime, Origin::Signed(alice.clone()), calls);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 50);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+		assert_eq!(runtime.balances.balance(&charlie), 20);
+		assert_eq!(runtime.system.events(), &[RuntimeEvent::utility(Event::BatchCompleted)]);
+	}
+
+	#[test]
+	fn batch_all_reverts_on_error() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let calls = vec![transfer(&bob, 30), transfer(&charlie, 200)];
+
+		// Act
+		let res = Pallet::batch_all(&mut runtime, Origin::Signed(alice.clone()), calls);
+
+		// Assert
+		assert_eq!(res, Err("Not enough funds."));
+		assert_eq!(runtime.balances.balance(&alice), 100);
+		assert_eq!(runtime.balances.balance(&bob), 0);
+		assert_eq!(runtime.balances.balance(&charlie), 0);
+		assert!(runtime.system.events().is_empty());
+	}
+
+	#[test]
+	fn batch_through_runtime_dispatch() {
+		// Arrange
+		let (mut runtime, alice, bob, charlie) = setup();
+		let call = RuntimeCall::utility(Call::batch_all {
+			calls: vec![
+				transfer(&bob, 30),
+				RuntimeCall::utility(Call::batch { calls: vec![transfer(&charlie, 20)] }),
+			],
+		});
+
+		// Act
+		let res = runtime.dispatch(Origin::Signed(alice.clone()), call);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 50);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+		assert_eq!(runtime.balances.balance(&charlie), 20);
+	}
+
+	fn transfer(to: &str, amount: u128) -> RuntimeCall {
+		RuntimeCall::balances(balances::Call::transfer { to: to.to_string(), amount })
+	}
+
+	fn setup() -> (Runtime, String, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let charlie = String::from("charlie");
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&alice, 100);
+
+		(runtime, alice, bob, charlie)
+	}
+}
@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+
+use num::{CheckedMul, CheckedSub, Zero};
+
+use crate::{
+	balances::{self, LockIdentifier},
+	support::{
+		DispatchResult, GetPallet, Hooks, OnRuntimeUpgrade, ProvideInherent, ValidateUnsigned,
+	},
+	system::{self, ensure_signed},
+};
+
+/// The identifier of the lock of the vesting balance.
+pub const VESTING_ID: LockIdentifier = *b"vesting ";
+
+pub trait Config:
+	balances::Config<Balance: From<<Self as system::Config>::BlockNumber> + CheckedMul>
+	+ system::Config<
+		BlockNumber: CheckedSub + Ord,
+		RuntimeEvent: From<
+			Event<<Self as system::Config>::AccountId, <Self as balances::Config>::Balance>,
+		>,
+	> + Sized
+{
+}
+
+/// A schedule of a balance unlocked linearly, block after block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VestingInfo<Balance, BlockNumber> {
+	/// The total balance locked by the schedule.
+	pub locked: Balance,
+	/// The balance unlocked at every block.
+	pub per_block: Balance,
+	/// The block at which the balance starts to unlock.
+	pub starting_block: BlockNumber,
+}
+
+impl<Balance, BlockNumber> VestingInfo<Balance, BlockNumber>
+where
+	Balance: From<BlockNumber> + CheckedMul + CheckedSub + Zero + Copy + Ord,
+	BlockNumber: CheckedSub + Copy + Ord,
+{
+	/// Get the balance which is still locked at the block `now`.
+	pub fn locked_at(&self, now: BlockNumber) -> Balance {
+		if now <= self.starting_block {
+			return self.locked
+		}
+		let vested = now
+			.checked_sub(&self.starting_block)
+			.and_then(|elapsed| Balance::from(elapsed).checked_mul(&self.per_block))
+			.unwrap_or(self.locked);
+		self.locked.checked_sub(&vested).unwrap_or(Balance::zero())
+	}
+}
+
+/// The events deposited by the Vesting Pallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<AccountId, Balance> {
+	/// The balance of `account` still locked by its schedule was updated to `unvested`.
+	VestingUpdated { account: AccountId, unvested: Balance },
+	/// The whole balance of the schedule of `account` was unlocked.
+	VestingCompleted { account: AccountId },
+}
+
+/// This is the Vesting Module.
+/// It locks a balance in the Balances Pallet, and unlocks it linearly over time. The unlocked part
+/// is only released when the owner of the balance calls `vest`.
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+	/// The vesting schedule of each account.
+	vesting: BTreeMap<T::AccountId, VestingInfo<T::Balance, T::BlockNumber>>,
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T>
+where
+	T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+{
+	/// Release the balance of the caller which has vested so far.
+	pub fn vest(runtime: &mut T, origin: T::RuntimeOrigin) -> DispatchResult {
+		let caller = ensure_signed(origin)?;
+		Self::update_lock(runtime, caller)
+	}
+
+	/// Transfer `schedule.locked` from the caller to `target`, locked by the vesting `schedule`.
+	/// This function fails if `target` already has a vesting schedule.
+	pub fn vested_transfer(
+		runtime: &mut T,
+		origin: T::RuntimeOrigin,
+		target: T::AccountId,
+		schedule: VestingInfo<T::Balance, T::BlockNumber>,
+	) -> DispatchResult {
+		Self::ensure_valid(runtime, &target, &schedule)?;
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		balances.transfer(origin, target.clone(), schedule.locked)?;
+
+		Self::add_vesting_schedule(runtime, &target, schedule)
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Vesting Pallet.
+	pub fn new() -> Self {
+		Self { vesting: BTreeMap::new() }
+	}
+
+	/// Get the vesting schedule of `who`, if any.
+	pub fn vesting(&self, who: &T::AccountId) -> Option<&VestingInfo<T::Balance, T::BlockNumber>> {
+		self.vesting.get(who)
+	}
+
+	/// Lock a part of the balance of `who` with the vesting `schedule`, for example at genesis.
+	/// This function fails if `who` already has a vesting schedule.
+	pub fn add_vesting_schedule(
+		runtime: &mut T,
+		who: &T::AccountId,
+		schedule: VestingInfo<T::Balance, T::BlockNumber>,
+	) -> DispatchResult
+	where
+		T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		Self::ensure_valid(runtime, who, &schedule)?;
+
+		let vesting: &mut Self = runtime.pallet_mut();
+		vesting.vesting.insert(who.clone(), schedule);
+		Self::update_lock(runtime, who.clone())
+	}
+
+	/// Check that `schedule` can be added for `who`.
+	fn ensure_valid(
+		runtime: &T,
+		who: &T::AccountId,
+		schedule: &VestingInfo<T::Balance, T::BlockNumber>,
+	) -> DispatchResult
+	where
+		T: GetPallet<Pallet<T>>,
+	{
+		if schedule.locked.is_zero() || schedule.per_block.is_zero() {
+			return Err("Vesting schedule must have a non zero amount and unlock per block")
+		}
+		let vesting: &Self = runtime.pallet();
+		if vesting.vesting.contains_key(who) {
+			return Err("Account already has a vesting schedule")
+		}
+		Ok(())
+	}
+
+	/// Update the lock of the balance of `who` to the part of its schedule which has not vested
+	/// yet at the current block, removing the schedule once it has fully vested.
+	fn update_lock(runtime: &mut T, who: T::AccountId) -> DispatchResult
+	where
+		T: GetPallet<Pallet<T>> + GetPallet<system::Pallet<T>> + GetPallet<balances::Pallet<T>>,
+	{
+		let system: &system::Pallet<T> = runtime.pallet();
+		let now = system.block_number();
+
+		let vesting: &mut Self = runtime.pallet_mut();
+		let schedule = vesting.vesting.get(&who).ok_or("Account has no vesting schedule")?;
+		let unvested = schedule.locked_at(now);
+		if unvested.is_zero() {
+			vesting.vesting.remove(&who);
+		}
+
+		let balances: &mut balances::Pallet<T> = runtime.pallet_mut();
+		let event = if unvested.is_zero() {
+			balances.remove_lock(VESTING_ID, &who);
+			Event::VestingCompleted { account: who }
+		} else {
+			balances.set_lock(VESTING_ID, &who, unvested);
+			Event::VestingUpdated { account: who, unvested }
+		};
+
+		let system: &mut system::Pallet<T> = runtime.pallet_mut();
+		system.deposit_event(event);
+		Ok(())
+	}
+}
+
+impl<T: Config> Hooks<T, T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> OnRuntimeUpgrade for Pallet<T> {}
+
+impl<T: Config> ProvideInherent<Call<T>> for Pallet<T> {}
+
+impl<T: Config> ValidateUnsigned<T, Call<T>> for Pallet<T> {}
+
+impl<T: Config> Default for Pallet<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{support::Origin, Runtime, RuntimeEvent};
+
+	#[test]
+	fn locked_at() {
+		// Arrange
+		let schedule = VestingInfo { locked: 50u128, per_block: 10, starting_block: 2u32 };
+
+		// Act & Assert
+		assert_eq!(schedule.locked_at(0), 50);
+		assert_eq!(schedule.locked_at(2), 50);
+		assert_eq!(schedule.locked_at(3), 40);
+		assert_eq!(schedule.locked_at(6), 10);
+		assert_eq!(schedule.locked_at(7), 0);
+		assert_eq!(schedule.locked_at(100), 0);
+	}
+
+	#[test]
+	fn vested_transfer_locks_balance() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+
+		// Act
+		let res = Pallet::vested_transfer(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			schedule(),
+		);
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.balance(&alice), 50);
+		assert_eq!(runtime.balances.balance(&bob), 50);
+		assert_eq!(runtime.balances.locked_balance(&bob), 50);
+		assert_eq!(runtime.vesting.vesting(&bob), Some(&schedule()));
+		assert_eq!(
+			runtime.balances.transfer(Origin::Signed(bob.clone()), alice, 10),
+			Err("Not enough unlocked funds.")
+		);
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::vesting(Event::VestingUpdated { account: bob, unvested: 50 })]
+		);
+	}
+
+	#[test]
+	fn vest_releases_vested_balance() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::vested_transfer(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			schedule(),
+		);
+		advance_to(&mut runtime, 5);
+
+		// Act
+		let res = Pallet::vest(&mut runtime, Origin::Signed(bob.clone()));
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.locked_balance(&bob), 20);
+		assert_eq!(runtime.balances.transfer(Origin::Signed(bob.clone()), alice, 30), Ok(()));
+		assert_eq!(runtime.vesting.vesting(&bob), Some(&schedule()));
+	}
+
+	#[test]
+	fn vest_completes_schedule() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::vested_transfer(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			schedule(),
+		);
+		advance_to(&mut runtime, 10);
+		runtime.system.reset_events();
+
+		// Act
+		let res = Pallet::vest(&mut runtime, Origin::Signed(bob.clone()));
+
+		// Assert
+		assert_eq!(res, Ok(()));
+		assert_eq!(runtime.balances.locked_balance(&bob), 0);
+		assert_eq!(runtime.vesting.vesting(&bob), None);
+		assert_eq!(
+			runtime.system.events(),
+			&[RuntimeEvent::vesting(Event::VestingCompleted { account: bob.clone() })]
+		);
+		assert_eq!(
+			Pallet::vest(&mut runtime, Origin::Signed(bob)),
+			Err("Account has no vesting schedule")
+		);
+	}
+
+	#[test]
+	fn vested_transfer_errors() {
+		// Arrange
+		let (mut runtime, alice, bob) = setup();
+		let _ = Pallet::vested_transfer(
+			&mut runtime,
+			Origin::Signed(alice.clone()),
+			bob.clone(),
+			schedule(),
+		);
+
+		// Act & Assert
+		assert_eq!(
+			Pallet::vested_transfer(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				bob.clone(),
+				schedule()
+			),
+			Err("Account already has a vesting schedule")
+		);
+		assert_eq!(
+			Pallet::vested_transfer(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				String::from("charlie"),
+				VestingInfo { per_block: 0, ..schedule() }
+			),
+			Err("Vesting schedule must have a non zero amount and unlock per block")
+		);
+		assert_eq!(
+			Pallet::vested_transfer(
+				&mut runtime,
+				Origin::Signed(alice.clone()),
+				String::from("charlie"),
+				VestingInfo { locked: 200, ..schedule() }
+			),
+			Err("Not enough funds.")
+		);
+		assert_eq!(runtime.balances.balance(&alice), 50);
+	}
+
+	fn schedule() -> VestingInfo<u128, u32> {
+		VestingInfo { locked: 50, per_block: 10, starting_block: 2 }
+	}
+
+	fn advance_to(runtime: &mut Runtime, block_number: u32) {
+		while runtime.system.block_number() < block_number {
+			runtime.system.inc_block_number();
+		}
+	}
+
+	fn setup() -> (Runtime, String, String) {
+		let alice = String::from("alice");
+		let bob = String::from("bob");
+		let mut runtime = Runtime::new();
+		runtime.balances.set_balance(&alice, 100);
+
+		(runtime, alice, bob)
+	}
+}